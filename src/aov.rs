@@ -0,0 +1,265 @@
+use macroquad::prelude::*;
+
+use crate::CartesianCoords3D;
+use crate::scene::image_from_colors;
+
+// First thing a ray hit, see StoppingCriterion. Unfinished rays ran out of integration steps
+// before hitting anything.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HitKind {
+    Unfinished,
+    Horizon,
+    Escaped,
+    Disk,
+    DiskVolume,
+    Star,
+}
+
+impl HitKind {
+    // Index of the kind in the hit buffer and in AOV_HIT_COLORS
+    pub fn code(&self) -> usize {
+        *self as usize
+    }
+}
+
+// What happened to the ray of a pixel, next to its colour. The hit, the disk radius and the
+// redshift g = E_observed / E_emitted all describe the first thing the ray met, which is in front
+// of whatever a translucent disk lets through, while the escape direction is where the ray left
+// the bounding box, if it did.
+#[derive(Debug, Clone, Copy)]
+pub struct RayRecord {
+    pub(crate) hit: HitKind,
+    pub(crate) steps: usize,   // accepted RKF45 steps
+    pub(crate) retries: usize, // rejected tries, including those of a failed step
+    pub(crate) min_radius: f64,
+    pub(crate) affine_length: f64,
+    pub(crate) escape_direction: Option<CartesianCoords3D>,
+    pub(crate) disk_radius: Option<f64>, // when the first hit is the disk
+    pub(crate) redshift: Option<f64>,
+}
+
+impl RayRecord {
+    pub fn hit(&self) -> HitKind {
+        self.hit
+    }
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+    pub fn retries(&self) -> usize {
+        self.retries
+    }
+    pub fn min_radius(&self) -> f64 {
+        self.min_radius
+    }
+    pub fn affine_length(&self) -> f64 {
+        self.affine_length
+    }
+    pub fn escape_direction(&self) -> Option<CartesianCoords3D> {
+        self.escape_direction
+    }
+    pub fn disk_radius(&self) -> Option<f64> {
+        self.disk_radius
+    }
+    pub fn redshift(&self) -> Option<f64> {
+        self.redshift
+    }
+}
+
+// What the viewer shows, the image itself or one of the AOVs in false colours
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AovMode {
+    Color,
+    Hit,
+    Redshift,
+    Steps,
+    Retries,
+    MinRadius,
+    AffineLength,
+    EscapeDirection,
+    DiskRadius,
+}
+
+impl AovMode {
+    const ALL: [AovMode; 9] = [
+        AovMode::Color,
+        AovMode::Hit,
+        AovMode::Redshift,
+        AovMode::Steps,
+        AovMode::Retries,
+        AovMode::MinRadius,
+        AovMode::AffineLength,
+        AovMode::EscapeDirection,
+        AovMode::DiskRadius,
+    ];
+
+    // Cycles through the modes
+    pub fn next(&self) -> Self {
+        let index = Self::ALL.iter().position(|mode| mode == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            AovMode::Color => "color",
+            AovMode::Hit => "hit",
+            AovMode::Redshift => "redshift",
+            AovMode::Steps => "steps",
+            AovMode::Retries => "retries",
+            AovMode::MinRadius => "min_radius",
+            AovMode::AffineLength => "affine_length",
+            AovMode::EscapeDirection => "escape_direction",
+            AovMode::DiskRadius => "disk_radius",
+        }
+    }
+}
+
+// Arbitrary output variables of an image, one float per pixel row by row, except for the
+// escape direction which has x, y, z. Missing values, like the disk radius of a ray that
+// didn't hit the disk, are NaN.
+#[derive(Debug, Clone)]
+pub struct AovBuffers {
+    width: u16,
+    height: u16,
+    hit: Vec<f32>, // HitKind::code
+    redshift: Vec<f32>,
+    steps: Vec<f32>,
+    retries: Vec<f32>,
+    min_radius: Vec<f32>,
+    affine_length: Vec<f32>,
+    escape_direction: Vec<f32>,
+    disk_radius: Vec<f32>,
+}
+
+impl AovBuffers {
+    pub fn from_records(records: &[RayRecord], width: u16, height: u16) -> Self {
+        let channel = |value: fn(&RayRecord) -> Option<f64>| -> Vec<f32> {
+            records
+                .iter()
+                .map(|record| value(record).map_or(f32::NAN, |value| value as f32))
+                .collect()
+        };
+        let escape_direction = records
+            .iter()
+            .flat_map(|record| match record.escape_direction {
+                Some(direction) => [direction.x(), direction.y(), direction.z()].map(|v| v as f32),
+                None => [f32::NAN; 3],
+            })
+            .collect();
+        Self {
+            width,
+            height,
+            hit: channel(|record| Some(record.hit.code() as f64)),
+            redshift: channel(|record| record.redshift),
+            steps: channel(|record| Some(record.steps as f64)),
+            retries: channel(|record| Some(record.retries as f64)),
+            min_radius: channel(|record| Some(record.min_radius)),
+            affine_length: channel(|record| Some(record.affine_length)),
+            escape_direction,
+            disk_radius: channel(|record| record.disk_radius),
+        }
+    }
+
+    pub fn width(&self) -> u16 {
+        self.width
+    }
+    pub fn height(&self) -> u16 {
+        self.height
+    }
+    pub fn hit(&self) -> &[f32] {
+        &self.hit
+    }
+    pub fn redshift(&self) -> &[f32] {
+        &self.redshift
+    }
+    pub fn steps(&self) -> &[f32] {
+        &self.steps
+    }
+    pub fn retries(&self) -> &[f32] {
+        &self.retries
+    }
+    pub fn min_radius(&self) -> &[f32] {
+        &self.min_radius
+    }
+    pub fn affine_length(&self) -> &[f32] {
+        &self.affine_length
+    }
+    pub fn escape_direction(&self) -> &[f32] {
+        &self.escape_direction
+    }
+    pub fn disk_radius(&self) -> &[f32] {
+        &self.disk_radius
+    }
+
+    // False colours of an AOV, None for AovMode::Color. Hits have one colour per kind,
+    // redshifts go from red to blue through white at g = 1 over a factor AOV_REDSHIFT_RANGE,
+    // escape directions map x, y, z to red, green, blue, and the other values run through
+    // a heat map between their smallest and largest finite values. Missing values are black.
+    pub fn visualise(&self, mode: AovMode) -> Option<Image> {
+        let colors: Vec<Color> = match mode {
+            AovMode::Color => return None,
+            AovMode::Hit => self
+                .hit
+                .iter()
+                .map(|&code| crate::AOV_HIT_COLORS[code as usize % crate::AOV_HIT_COLORS.len()])
+                .collect(),
+            AovMode::Redshift => self.redshift.iter().map(|&g| redshift_color(g)).collect(),
+            AovMode::EscapeDirection => self
+                .escape_direction
+                .chunks_exact(3)
+                .map(|direction| match direction {
+                    [x, y, z] if !x.is_nan() => {
+                        Color::new(0.5 * (x + 1.), 0.5 * (y + 1.), 0.5 * (z + 1.), 1.)
+                    }
+                    _ => BLACK,
+                })
+                .collect(),
+            AovMode::Steps => heat_map(&self.steps),
+            AovMode::Retries => heat_map(&self.retries),
+            AovMode::MinRadius => heat_map(&self.min_radius),
+            AovMode::AffineLength => heat_map(&self.affine_length),
+            AovMode::DiskRadius => heat_map(&self.disk_radius),
+        };
+        Some(image_from_colors(&colors, self.width, self.height))
+    }
+}
+
+// Red for redshifted light, g < 1, blue for blueshifted light, saturated at the ends of the range
+fn redshift_color(redshift: f32) -> Color {
+    if redshift.is_nan() || redshift <= 0. {
+        return BLACK;
+    }
+    let level = (redshift.log2() / (crate::AOV_REDSHIFT_RANGE as f32).log2()).clamp(-1., 1.);
+    if level >= 0. {
+        Color::new(1. - level, 1. - level, 1., 1.)
+    } else {
+        Color::new(1., 1. + level, 1. + level, 1.)
+    }
+}
+
+// Blue, cyan, green, yellow, red from the smallest to the largest value
+fn heat_map(values: &[f32]) -> Vec<Color> {
+    let (min, max) = values
+        .iter()
+        .filter(|value| value.is_finite())
+        .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), &value| {
+            (min.min(value), max.max(value))
+        });
+    let range = (max - min).max(f32::EPSILON);
+    values
+        .iter()
+        .map(|&value| {
+            if !value.is_finite() {
+                return BLACK;
+            }
+            let level = ((value - min) / range).clamp(0., 1.) * 4.;
+            let fraction = level.fract();
+            match level as u32 {
+                0 => Color::new(0., fraction, 1., 1.),
+                1 => Color::new(0., 1., 1. - fraction, 1.),
+                2 => Color::new(fraction, 1., 0., 1.),
+                3 => Color::new(1., 1. - fraction, 0., 1.),
+                _ => Color::new(1., 0., 0., 1.),
+            }
+        })
+        .collect()
+}
@@ -3,18 +3,20 @@ use std::error::Error;
 use std::future::Future;
 use std::sync::Arc;
 
-use crate::{BlackHole, Hyperparameters, Scene, Skybox, black_hole::AccretionDisk, scene::Camera};
+use crate::{
+    BlackHole, Hyperparameters, Metric, Scene, Skybox, black_hole::AccretionDisk, scene::Camera,
+};
 
 pub trait Backend: Sized {
     fn new() -> impl Future<Output = Result<Self, Box<dyn Error>>> + Send;
 
-    fn compute(
+    fn compute<M: Metric>(
         &mut self,
         accretion_disk: &AccretionDisk,
         black_hole: &BlackHole,
         skybox: Arc<Skybox>,
         camera: &Camera,
-        scene: &Scene,
+        scene: &Scene<M>,
         hyperparams: &Hyperparameters,
     ) -> Result<Image, Box<dyn Error>>;
 
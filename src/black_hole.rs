@@ -1,25 +1,126 @@
 use macroquad::prelude::*;
+use std::f64::consts::PI;
 
-use crate::{CartesianCoords3D, CartesianCoords4D};
+use crate::spectrum::Spectrum;
+use crate::{CartesianCoords3D, CartesianCoords4D, Norm};
+use crate::{DiskTexture, HotSpot, MagneticField};
 
 fn smoothstep(edge0: f64, edge1: f64, x: f64) -> f64 {
     let t = ((x - edge0) / (edge1 - edge0)).max(0.0).min(1.0);
     t * t * (3.0 - 2.0 * t)
 }
 
+// Radial profile of the flux emitted by the disk
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EmissivityProfile {
+    // Newtonian thin disk, (1 - sqrt(r_isco / r)) / r³
+    ShakuraSunyaev,
+    // Relativistic thin disk (Page & Thorne 1974), depends on the spin and charge of the black hole
+    NovikovThorne,
+}
+
+// Point where a ray goes through a thin disk
+#[derive(Debug, Clone, Copy)]
+pub struct DiskCrossing {
+    position: CartesianCoords3D,
+    delay: f64,         // light travel time to the camera
+    cos_incidence: f64, // |cos| of the angle between the ray and the normal of the disk
+    order: usize,       // number of times the ray went through the equatorial plane before
+}
+
+impl DiskCrossing {
+    pub fn new(position: CartesianCoords3D, delay: f64, cos_incidence: f64, order: usize) -> Self {
+        Self {
+            position,
+            delay,
+            cos_incidence,
+            order,
+        }
+    }
+
+    pub fn position(&self) -> CartesianCoords3D {
+        self.position
+    }
+    pub fn radius(&self) -> f64 {
+        self.position.x().hypot(self.position.y())
+    }
+    pub fn delay(&self) -> f64 {
+        self.delay
+    }
+    pub fn cos_incidence(&self) -> f64 {
+        self.cos_incidence
+    }
+    pub fn order(&self) -> usize {
+        self.order
+    }
+}
+
+// Point of a ray inside a thick disk: cylindrical radius, azimuth, height above the equatorial
+// plane, the length of ray it stands for, the light travel time to the camera and the number of
+// times the ray went through the equatorial plane before
+#[derive(Debug, Clone, Copy)]
+pub struct DiskSample {
+    radius: f64,
+    azimuth: f64,
+    height: f64,
+    length: f64,
+    delay: f64,
+    order: usize,
+}
+
+impl DiskSample {
+    pub fn radius(&self) -> f64 {
+        self.radius
+    }
+    pub fn azimuth(&self) -> f64 {
+        self.azimuth
+    }
+    pub fn height(&self) -> f64 {
+        self.height
+    }
+    pub fn length(&self) -> f64 {
+        self.length
+    }
+    pub fn delay(&self) -> f64 {
+        self.delay
+    }
+    pub fn order(&self) -> usize {
+        self.order
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct AccretionDisk {
+    mass: f64,
+    spin: f64,
+    charge: f64,
+    emissivity: EmissivityProfile,
     r_isco: f64,
     accretion_r_max: f64,
     width: f64,
     max_temperature: f64,
     step_opacity: f64,
     doppler_factor: f64,
-    fade_start_ratio: f64, // At which radius do we start to fade out
-    peak_brigthness: f64,  // highest brightness
+    fade_start_ratio: f64,       // At which radius do we start to fade out
+    peak_brigthness: f64,        // highest brightness
+    aspect_ratio: f64,           // scale height H / r, 0 for an infinitely thin disk
+    vertical_optical_depth: f64, // optical depth across the disk where it is the brightest
+    texture: Option<DiskTexture>,
+    hot_spot: Option<HotSpot>,
+    magnetic_field: MagneticField, // sets the polarisation of the emitted light
+    time: f64, // coordinate time at the camera, light from the disk left it earlier
 }
 
 impl AccretionDisk {
+    pub fn mass(&self) -> f64 {
+        self.mass
+    }
+    pub fn spin(&self) -> f64 {
+        self.spin
+    }
+    pub fn charge(&self) -> f64 {
+        self.charge
+    }
     pub fn r_isco(&self) -> f64 {
         self.r_isco
     }
@@ -44,12 +145,47 @@ impl AccretionDisk {
     pub fn peak_brigthness(&self) -> f64 {
         self.peak_brigthness
     }
+    pub fn emissivity(&self) -> EmissivityProfile {
+        self.emissivity
+    }
+    pub fn aspect_ratio(&self) -> f64 {
+        self.aspect_ratio
+    }
+    pub fn vertical_optical_depth(&self) -> f64 {
+        self.vertical_optical_depth
+    }
+    pub fn is_thick(&self) -> bool {
+        self.aspect_ratio > 0.
+    }
+    pub fn scale_height(&self, radius: f64) -> f64 {
+        self.aspect_ratio * radius
+    }
+    pub fn texture(&self) -> Option<DiskTexture> {
+        self.texture
+    }
+    pub fn hot_spot(&self) -> Option<HotSpot> {
+        self.hot_spot
+    }
+    pub fn magnetic_field(&self) -> MagneticField {
+        self.magnetic_field
+    }
+    pub fn time(&self) -> f64 {
+        self.time
+    }
+
+    // Keplerian angular velocity of the gas in Boyer-Lindquist coordinates (Kerr-Newman)
+    pub fn angular_velocity(&self, radius: f64) -> f64 {
+        let root = (self.mass * radius - self.charge.powi(2)).max(0.).sqrt();
+        root / (radius.powi(2) + self.spin * root)
+    }
 
-    pub fn new(rs: f64) -> Self {
-        let r_isco = rs * 3.;
+    pub fn new(rs: f64, spin: f64, charge: f64, r_isco: f64) -> Self {
         let accretion_r_max = rs * 15.;
-        let r_peak = r_isco * (49.0 / 36.0);
         Self {
+            mass: rs / 2.,
+            spin,
+            charge,
+            emissivity: EmissivityProfile::ShakuraSunyaev,
             r_isco,
             accretion_r_max,
             width: accretion_r_max - r_isco,
@@ -57,15 +193,75 @@ impl AccretionDisk {
             step_opacity: 0.04,
             doppler_factor: 3.0,
             fade_start_ratio: 0.5,
-            peak_brigthness: Self::brightness(r_isco, r_peak),
+            peak_brigthness: 0.,
+            aspect_ratio: 0.,
+            vertical_optical_depth: 5.,
+            texture: None,
+            hot_spot: None,
+            magnetic_field: MagneticField::VERTICAL,
+            time: 0.,
         }
+        .with_emissivity(EmissivityProfile::ShakuraSunyaev)
     }
 
+    pub fn with_emissivity(self, emissivity: EmissivityProfile) -> Self {
+        let disk = Self { emissivity, ..self };
+        let peak_brigthness = match emissivity {
+            // Peaks at r = 49/36 r_isco
+            EmissivityProfile::ShakuraSunyaev => {
+                Self::brightness(self.r_isco, self.r_isco * (49.0 / 36.0))
+            }
+            // No closed form for the peak, scan the disk
+            EmissivityProfile::NovikovThorne => (0..=1000)
+                .map(|i| disk.flux(self.r_isco + self.width * i as f64 / 1000.))
+                .fold(0., f64::max),
+        };
+        Self {
+            peak_brigthness,
+            ..disk
+        }
+    }
+
+    // Gaussian vertical profile of scale height aspect_ratio * r. An aspect ratio of 0 keeps the thin disk.
+    pub fn with_thickness(self, aspect_ratio: f64, vertical_optical_depth: f64) -> Self {
+        Self {
+            aspect_ratio: aspect_ratio.max(0.),
+            vertical_optical_depth: vertical_optical_depth.max(0.),
+            ..self
+        }
+    }
+
+    pub fn with_texture(self, texture: DiskTexture) -> Self {
+        Self {
+            texture: Some(texture),
+            ..self
+        }
+    }
+
+    pub fn with_hot_spot(self, hot_spot: HotSpot) -> Self {
+        Self {
+            hot_spot: Some(hot_spot),
+            ..self
+        }
+    }
+
+    pub fn with_magnetic_field(self, magnetic_field: MagneticField) -> Self {
+        Self {
+            magnetic_field,
+            ..self
+        }
+    }
+
+    pub fn at_time(self, time: f64) -> Self {
+        Self { time, ..self }
+    }
+
+    // Point where the segment crosses the disk plane, and the fraction of the segment before it
     pub fn check_intersection(
         &self,
         position1: CartesianCoords3D,
         position2: CartesianCoords3D,
-    ) -> Option<f64> {
+    ) -> Option<(CartesianCoords3D, f64)> {
         let t = -position1.z() / (position2.z() - position1.z());
         if t < 0. || t > 1. {
             return None;
@@ -76,75 +272,295 @@ impl AccretionDisk {
             return None;
         }
 
-        Some(r_plane)
+        Some((equator_collision, t))
     }
 
-    fn kelvin_to_rgb(temp_kelvin: f64) -> (f64, f64, f64) {
-        let temp = temp_kelvin / 100.0;
-        let red = if temp <= 66.0 {
-            255.0
-        } else {
-            329.698727446 * (temp - 60.0).powf(-0.1332047592)
-        };
-        let green = if temp <= 66.0 {
-            99.4708025861 * temp.ln() - 161.1195681661
+    // Samples the straight segment between two positions where it goes through a thick disk,
+    // front to back. Samples are spaced by a fraction of the scale height at the ISCO.
+    // Delays are the light travel times from both ends of the segment to the camera, and order
+    // the number of times the ray went through the equatorial plane before the segment.
+    // Fractions of the segment between which it is inside the slab containing the whole
+    // disk, None if it misses the slab
+    pub fn slab_overlap(
+        &self,
+        position1: CartesianCoords3D,
+        position2: CartesianCoords3D,
+    ) -> Option<(f64, f64)> {
+        let extent = crate::DISK_VOLUME_EXTENT * self.scale_height(self.accretion_r_max);
+        let (z1, dz) = (position1.z(), position2.z() - position1.z());
+        let (t_start, t_end) = if dz.abs() < crate::DIV_EPSILON {
+            if z1.abs() > extent {
+                return None;
+            }
+            (0., 1.)
         } else {
-            288.1221695283 * (temp - 60.0).powf(-0.0755148492)
+            let (t_low, t_high) = ((-extent - z1) / dz, (extent - z1) / dz);
+            (t_low.min(t_high).max(0.), t_low.max(t_high).min(1.))
         };
-        let blue = if temp <= 19.0 {
-            0.0
-        } else if temp >= 66.0 {
-            255.0
-        } else {
-            138.5177312231 * (temp - 10.0).ln() - 305.0447927307
+        (t_start < t_end).then_some((t_start, t_end))
+    }
+
+    pub fn sample_volume(
+        &self,
+        position1: CartesianCoords3D,
+        position2: CartesianCoords3D,
+        delay1: f64,
+        delay2: f64,
+        order: usize,
+    ) -> Vec<DiskSample> {
+        let Some((t_start, t_end)) = self.slab_overlap(position1, position2) else {
+            return Vec::new();
         };
-        (
-            (red.clamp(0., 255.)) / 255.0,
-            (green.clamp(0., 255.)) / 255.0,
-            (blue.clamp(0., 255.)) / 255.0,
-        )
+        let z1 = position1.z();
+
+        let segment = position2 - position1;
+        let length = segment.norm() * (t_end - t_start);
+        let spacing = self.scale_height(self.r_isco) / crate::DISK_VOLUME_SAMPLES_PER_SCALE_HEIGHT;
+        let num_samples =
+            ((length / spacing).ceil() as usize).clamp(1, crate::DISK_VOLUME_MAX_SAMPLES);
+
+        (0..num_samples)
+            .filter_map(|i| {
+                let t = t_start + (t_end - t_start) * (i as f64 + 0.5) / num_samples as f64;
+                let position = position1 + segment * t;
+                let radius = position.x().hypot(position.y());
+                let azimuth = position.y().atan2(position.x());
+                let height = position.z();
+                if radius < self.r_isco
+                    || radius > self.accretion_r_max
+                    || height.abs() > crate::DISK_VOLUME_EXTENT * self.scale_height(radius)
+                {
+                    return None;
+                }
+                Some(DiskSample {
+                    radius,
+                    azimuth,
+                    height,
+                    length: length / num_samples as f64,
+                    delay: delay1 + t * (delay2 - delay1),
+                    order: if (height < 0.) == (z1 < 0.) {
+                        order
+                    } else {
+                        order + 1
+                    },
+                })
+            })
+            .collect()
     }
 
     fn brightness(r_isco: f64, radius: f64) -> f64 {
         (1.0 - (r_isco / radius).sqrt()) / (radius.powi(3) + crate::DIV_EPSILON)
     }
 
-    pub fn get_color(&self, radius: f64) -> Option<Color> {
-        // x_pos for Doppler effect
+    fn novikov_thorne_brightness(mass: f64, spin: f64, r_isco: f64, radius: f64) -> f64 {
+        // Page & Thorne (1974) for Kerr, with x = sqrt(r / M) and a = J / M²
+        let a = (spin / mass).clamp(-1., 1.);
+        let x = (radius / mass).sqrt();
+        let x0 = (r_isco / mass).sqrt();
+        if x <= x0 {
+            return 0.;
+        }
+
+        // Roots of x³ - 3x + 2a = 0
+        let angle = a.acos();
+        let roots = [
+            2. * ((angle - PI) / 3.).cos(),
+            2. * ((angle + PI) / 3.).cos(),
+            -2. * (angle / 3.).cos(),
+        ];
+
+        let mut bracket = x - x0 - 1.5 * a * (x / x0).ln();
+        for (i, &root) in roots.iter().enumerate() {
+            // (root - a)² / root vanishes with a for the root going to 0
+            if root.abs() < crate::DIV_EPSILON {
+                continue;
+            }
+            let product: f64 = roots
+                .iter()
+                .enumerate()
+                .filter(|&(j, _)| j != i)
+                .map(|(_, &other)| root - other)
+                .product();
+            bracket -= 3. * (root - a).powi(2) / (root * product) * ((x - root) / (x0 - root)).ln();
+        }
+
+        // F = 3 Ṁ / (8π M r) * bracket / (x² (x³ - 3x + 2a)), without the constant factors
+        bracket / (radius * x.powi(2) * (x.powi(3) - 3. * x + 2. * a))
+    }
+
+    // Angular velocity, energy and angular momentum per unit mass of the circular orbit of the
+    // gas, from the equatorial Kerr-Newman metric
+    fn circular_orbit(&self, radius: f64) -> (f64, f64, f64) {
+        let (a, r) = (self.spin, radius);
+        let potential = (2. * self.mass * r - self.charge.powi(2)) / r.powi(2);
+        let g_tt = -(1. - potential);
+        let g_tphi = -a * potential;
+        let g_phiphi = r.powi(2) + a.powi(2) + a.powi(2) * potential;
+        let omega = self.angular_velocity(r);
+        let norm = -(g_tt + 2. * g_tphi * omega + g_phiphi * omega.powi(2));
+        let ut = 1. / norm.max(crate::DIV_EPSILON).sqrt();
+        let energy = -(g_tt + g_tphi * omega) * ut;
+        let angular_momentum = (g_tphi + g_phiphi * omega) * ut;
+        (omega, energy, angular_momentum)
+    }
+
+    // Page & Thorne (1974) for any circular orbits, here those of Kerr-Newman:
+    // F ∝ -Ω' / (√-g (E - ΩL)²) ∫ (E - ΩL) L' dr from the ISCO, with √-g = r for the metric of
+    // the equatorial plane.
+    // Derivatives are central differences and the integral uses the trapezoidal rule.
+    fn page_thorne_brightness(&self, radius: f64) -> f64 {
+        if radius <= self.r_isco {
+            return 0.;
+        }
+        let derivative = |f: &dyn Fn(f64) -> f64, r: f64| {
+            let h = r * crate::NOVIKOV_THORNE_DIFFERENCE_STEP;
+            (f(r + h) - f(r - h)) / (2. * h)
+        };
+        let binding = |r: f64| {
+            let (omega, energy, angular_momentum) = self.circular_orbit(r);
+            energy - omega * angular_momentum
+        };
+        let angular_momentum = |r: f64| self.circular_orbit(r).2;
+        let integrand = |r: f64| binding(r) * derivative(&angular_momentum, r);
+
+        let steps = crate::NOVIKOV_THORNE_INTEGRATION_STEPS;
+        let dr = (radius - self.r_isco) / steps as f64;
+        let integral: f64 = (0..=steps)
+            .map(|i| {
+                let weight = if i == 0 || i == steps { 0.5 } else { 1. };
+                weight * integrand(self.r_isco + dr * i as f64)
+            })
+            .sum::<f64>()
+            * dr;
+
+        let omega_derivative = derivative(&|r| self.circular_orbit(r).0, radius);
+        -omega_derivative / (radius * binding(radius).powi(2)) * integral
+    }
+
+    fn flux(&self, radius: f64) -> f64 {
+        match self.emissivity {
+            EmissivityProfile::ShakuraSunyaev => Self::brightness(self.r_isco, radius),
+            // The closed form only holds without charge
+            EmissivityProfile::NovikovThorne if self.charge == 0. => {
+                Self::novikov_thorne_brightness(self.mass, self.spin, self.r_isco, radius)
+            }
+            EmissivityProfile::NovikovThorne => self.page_thorne_brightness(radius),
+        }
+    }
+
+    // Flux relative to the peak and fade-out towards the outer edge at the given position,
+    // as it was when light arriving at the camera after the given delay left it
+    fn profile(&self, radius: f64, azimuth: f64, delay: f64) -> Option<(f64, f64)> {
         if radius < self.r_isco || radius > self.accretion_r_max {
             return None;
         }
 
-        // Shakura-Sunyaev
+        // Each ring has turned by Ω t since the texture was laid down
+        let emission_time = self.time - delay;
+        let texture = self.texture.map_or(1., |texture| {
+            texture.sample(
+                radius,
+                azimuth - self.angular_velocity(radius) * emission_time,
+            )
+        });
+        let hot_spot = self.hot_spot.map_or(0., |hot_spot| {
+            let angular_velocity = self.angular_velocity(hot_spot.orbit_radius());
+            hot_spot.flux(radius, azimuth, angular_velocity, emission_time)
+        });
         let normalized_brightness =
-            (Self::brightness(self.r_isco, radius) / self.peak_brigthness).min(1.0);
+            (self.flux(radius) / self.peak_brigthness).min(1.0) * texture + hot_spot;
 
         let normalized_radius = (radius - self.r_isco) / self.width;
         let geometric_falloff = 1.0 - smoothstep(self.fade_start_ratio, 1.0, normalized_radius);
 
-        // Stefan-Boltzmann's Law
-        let temp_k = self.max_temperature * normalized_brightness.powf(0.25);
-        // Planck's Law
-        let (r, g, b) = Self::kelvin_to_rgb(temp_k);
+        Some((normalized_brightness, geometric_falloff))
+    }
+
+    // Observed temperature, geometric falloff and opacity of the disk at the given position.
+    // redshift is g = E_observed / E_emitted, see Photon::redshift
+    fn emission(&self, crossing: &DiskCrossing, redshift: f64) -> Option<(f64, f64, f64)> {
+        let position = crossing.position;
+        let azimuth = position.y().atan2(position.x());
+        let (normalized_brightness, geometric_falloff) =
+            self.profile(crossing.radius(), azimuth, crossing.delay)?;
+
+        // Stefan-Boltzmann's Law, the observer sees a blackbody at g T
+        let temp_k = self.max_temperature * normalized_brightness.powf(0.25) * redshift;
 
-        // Doppler effect for later
-        // let doppler_boost = (1.0 - x_pos / r_out).powf(doppler_factor);
-        let doppler_boost = 1.0; // Disabled for now
+        // Thin limit of the thick disk: a ray going through it at an angle i from the normal
+        // sees the vertical optical depth stretched by 1 / |cos i|
+        let optical_depth = self.vertical_optical_depth * normalized_brightness * geometric_falloff
+            / crossing.cos_incidence.max(crate::DIV_EPSILON);
+        let opacity = -(-optical_depth).exp_m1();
 
-        let emitted_intensity = normalized_brightness * doppler_boost * geometric_falloff;
+        Some((temp_k, geometric_falloff, opacity))
+    }
+
+    // Observed temperature and opacity of a sample of a thick disk. The gas is in thermal
+    // equilibrium, so it emits blackbody radiation at its temperature and absorbs it with
+    // an optical depth dτ, which makes the sample contribute B(g T) (1 - e^-dτ).
+    // The density follows the flux profile, with a Gaussian vertical profile.
+    fn volume_emission(&self, sample: &DiskSample, redshift: f64) -> Option<(f64, f64)> {
+        let (normalized_brightness, geometric_falloff) =
+            self.profile(sample.radius, sample.azimuth, sample.delay)?;
+        let scale_height = self.scale_height(sample.radius).max(crate::DIV_EPSILON);
+
+        let temp_k = self.max_temperature * normalized_brightness.powf(0.25) * redshift;
+
+        let vertical_density = (-0.5 * (sample.height / scale_height).powi(2)).exp()
+            / (scale_height * (2. * PI).sqrt());
+        let optical_depth = self.vertical_optical_depth
+            * normalized_brightness
+            * geometric_falloff
+            * vertical_density
+            * sample.length;
+
+        Some((temp_k, -(-optical_depth).exp_m1()))
+    }
 
-        let emitted_r = r * emitted_intensity;
-        let emitted_g = g * emitted_intensity;
-        let emitted_b = b * emitted_intensity;
-        let opacity_for_step = self.step_opacity * emitted_intensity;
+    pub fn get_color(&self, crossing: &DiskCrossing, redshift: f64) -> Option<Color> {
+        let (temp_k, geometric_falloff, opacity) = self.emission(crossing, redshift)?;
+
+        // Planck's Law. The radiance already grows with the temperature, which accounts for
+        // both the flux profile and the relativistic beaming.
+        let (r, g, b) = crate::spectrum::blackbody_rgb(temp_k, self.max_temperature);
+
+        let emitted_r = r * geometric_falloff;
+        let emitted_g = g * geometric_falloff;
+        let emitted_b = b * geometric_falloff;
 
         Some(Color::new(
             emitted_r as f32,
             emitted_g as f32,
             emitted_b as f32,
-            opacity_for_step as f32,
+            opacity as f32,
         ))
     }
+
+    // Same as get_color, as a spectrum and its opacity
+    pub fn get_spectrum(&self, crossing: &DiskCrossing, redshift: f64) -> Option<(Spectrum, f32)> {
+        let (temp_k, geometric_falloff, opacity) = self.emission(crossing, redshift)?;
+
+        // g⁵ B_λ(g λ, T) = B_λ(λ, g T), so the shifted spectrum is the blackbody at the observed temperature
+        let spectrum = Spectrum::blackbody(temp_k, self.max_temperature) * geometric_falloff;
+        Some((spectrum, opacity as f32))
+    }
+
+    pub fn get_volume_color(&self, sample: &DiskSample, redshift: f64) -> Option<Color> {
+        let (temp_k, opacity) = self.volume_emission(sample, redshift)?;
+        let (r, g, b) = crate::spectrum::blackbody_rgb(temp_k, self.max_temperature);
+        Some(Color::new(r as f32, g as f32, b as f32, opacity as f32))
+    }
+
+    pub fn get_volume_spectrum(
+        &self,
+        sample: &DiskSample,
+        redshift: f64,
+    ) -> Option<(Spectrum, f32)> {
+        let (temp_k, opacity) = self.volume_emission(sample, redshift)?;
+        let spectrum = Spectrum::blackbody(temp_k, self.max_temperature);
+        Some((spectrum, opacity as f32))
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -154,6 +570,8 @@ pub struct BlackHole {
     visual_radius: f64,
     accretion_disk: AccretionDisk,
     mass: f64,
+    spin: f64,
+    charge: f64,
     color: Color,
 }
 
@@ -163,15 +581,39 @@ impl BlackHole {
     }
 
     pub fn new(coords: CartesianCoords4D, mass: f64) -> Self {
+        Self::kerr(coords, mass, 0.)
+    }
+
+    // Spin is the Kerr parameter a = J / M, in the same length unit as the mass (|a| <= M).
+    // A negative spin means the black hole rotates against the accretion disk.
+    pub fn kerr(coords: CartesianCoords4D, mass: f64, spin: f64) -> Self {
+        Self::kerr_newman(coords, mass, spin, 0.)
+    }
+
+    // Charge is Q in geometrised Gaussian units, in the same length unit as the mass (|Q| <= M).
+    pub fn reissner_nordstrom(coords: CartesianCoords4D, mass: f64, charge: f64) -> Self {
+        Self::kerr_newman(coords, mass, 0., charge)
+    }
+
+    // Spinning and charged black hole, a² + Q² <= M²
+    pub fn kerr_newman(coords: CartesianCoords4D, mass: f64, spin: f64, charge: f64) -> Self {
+        let spin = spin.clamp(-mass, mass);
+        let max_charge = (mass.powi(2) - spin.powi(2)).sqrt();
+        let charge = charge.clamp(-max_charge, max_charge);
         let radius = Self::compute_schwarzschild_radius(&mass);
-        let accretion_disk = AccretionDisk::new(radius);
-        let visual_radius = radius * crate::BLACK_HOLE_COLORED_SPHERE_RADIUS_FACTOR;
+        let (_, horizon_radius) = Self::compute_horizon_radii(&mass, &spin, &charge);
+        // The disk always orbits towards +phi
+        let r_isco = Self::compute_isco_radius(&mass, &spin, &charge, spin >= 0.);
+        let accretion_disk = AccretionDisk::new(radius, spin, charge, r_isco);
+        let visual_radius = horizon_radius * crate::BLACK_HOLE_COLORED_SPHERE_RADIUS_FACTOR;
         Self {
             coords,
             radius,
             visual_radius,
             accretion_disk,
             mass,
+            spin,
+            charge,
             color: BLACK,
         }
     }
@@ -184,14 +626,107 @@ impl BlackHole {
         self.mass
     }
 
+    pub fn spin(&self) -> f64 {
+        self.spin
+    }
+
+    pub fn charge(&self) -> f64 {
+        self.charge
+    }
+
     pub fn radius(&self) -> f64 {
         self.radius
     }
 
+    pub fn horizon_radius(&self) -> f64 {
+        let (_, outer) = Self::compute_horizon_radii(&self.mass, &self.spin, &self.charge);
+        outer
+    }
+
+    pub fn inner_horizon_radius(&self) -> f64 {
+        let (inner, _) = Self::compute_horizon_radii(&self.mass, &self.spin, &self.charge);
+        inner
+    }
+
+    // Circular photon orbit of a non-rotating hole: 3M for Schwarzschild, shrinking with charge
+    pub fn photon_sphere_radius(&self) -> f64 {
+        let discriminant = (9. * self.mass.powi(2) - 8. * self.charge.powi(2)).max(0.);
+        (3. * self.mass + discriminant.sqrt()) / 2.
+    }
+
+    pub fn isco_radius(&self, prograde: bool) -> f64 {
+        Self::compute_isco_radius(&self.mass, &self.spin, &self.charge, prograde)
+    }
+
+    // Angular velocity Ω and u^t of gas on a circular equatorial orbit towards +phi,
+    // in Boyer-Lindquist coordinates (Kerr-Newman)
+    pub fn keplerian_orbit(&self, radius: f64) -> (f64, f64) {
+        let (r, a) = (radius, self.spin);
+        let r2 = r.powi(2);
+        let omega = self.accretion_disk.angular_velocity(radius);
+
+        let potential = (2. * self.mass * r - self.charge.powi(2)) / r2;
+        let g_tt = -(1. - potential);
+        let g_tphi = -a * potential;
+        let g_phiphi = r2 + a.powi(2) * (1. + potential);
+        let norm = g_tt + 2. * omega * g_tphi + omega.powi(2) * g_phiphi;
+
+        (omega, 1. / (-norm).max(crate::DIV_EPSILON).sqrt())
+    }
+
+    pub fn orbital_period(&self, radius: f64) -> f64 {
+        2. * PI / self.accretion_disk.angular_velocity(radius)
+    }
+
     pub fn accretion_disk(&self) -> AccretionDisk {
         self.accretion_disk
     }
 
+    pub fn with_disk_emissivity(self, emissivity: EmissivityProfile) -> Self {
+        Self {
+            accretion_disk: self.accretion_disk.with_emissivity(emissivity),
+            ..self
+        }
+    }
+
+    pub fn with_thick_disk(self, aspect_ratio: f64, vertical_optical_depth: f64) -> Self {
+        Self {
+            accretion_disk: self
+                .accretion_disk
+                .with_thickness(aspect_ratio, vertical_optical_depth),
+            ..self
+        }
+    }
+
+    pub fn with_disk_texture(self, texture: DiskTexture) -> Self {
+        Self {
+            accretion_disk: self.accretion_disk.with_texture(texture),
+            ..self
+        }
+    }
+
+    pub fn with_hot_spot(self, hot_spot: HotSpot) -> Self {
+        Self {
+            accretion_disk: self.accretion_disk.with_hot_spot(hot_spot),
+            ..self
+        }
+    }
+
+    pub fn with_magnetic_field(self, magnetic_field: MagneticField) -> Self {
+        Self {
+            accretion_disk: self.accretion_disk.with_magnetic_field(magnetic_field),
+            ..self
+        }
+    }
+
+    // Same black hole with its disk as seen at the given coordinate time
+    pub fn at_time(self, time: f64) -> Self {
+        Self {
+            accretion_disk: self.accretion_disk.at_time(time),
+            ..self
+        }
+    }
+
     pub fn visual_radius(&self) -> f64 {
         self.visual_radius
     }
@@ -204,4 +739,127 @@ impl BlackHole {
         // In normalised units (G = c = 1)
         2. * mass
     }
+
+    fn compute_horizon_radii(mass: &f64, spin: &f64, charge: &f64) -> (f64, f64) {
+        // r± = M ± sqrt(M² - a² - Q²)
+        let root = (mass.powi(2) - spin.powi(2) - charge.powi(2))
+            .max(0.)
+            .sqrt();
+        (mass - root, mass + root)
+    }
+
+    fn compute_isco_radius(mass: &f64, spin: &f64, charge: &f64, prograde: bool) -> f64 {
+        if *charge != 0. {
+            // Prograde orbits see a positive spin
+            let spin = if prograde { spin.abs() } else { -spin.abs() };
+            return Self::compute_charged_isco_radius(mass, &spin, charge);
+        }
+
+        // Bardeen, Press & Teukolsky (1972). Prograde orbits co-rotate with the black hole.
+        let chi = spin / mass;
+        let z1 = 1. + (1. - chi.powi(2)).cbrt() * ((1. + chi).cbrt() + (1. - chi).cbrt());
+        let z2 = (3. * chi.powi(2) + z1.powi(2)).sqrt();
+        let root = ((3. - z1) * (3. + z1 + 2. * z2)).max(0.).sqrt();
+        if prograde {
+            mass * (3. + z2 - root)
+        } else {
+            mass * (3. + z2 + root)
+        }
+    }
+
+    fn compute_charged_isco_radius(mass: &f64, spin: &f64, charge: &f64) -> f64 {
+        // Kerr-Newman, for an orbit towards +phi: largest root of
+        // Mr (6Mr - r² - 9Q² + 3a²) + 4Q² (Q² - a²) - 8a (Mr - Q²)^3/2 = 0,
+        // found by Newton's method from 9M, above the ISCO of any orbit
+        let (a, q2) = (*spin, charge.powi(2));
+        let mut r = 9. * mass;
+        for _ in 0..64 {
+            let root = (mass * r - q2).max(0.).sqrt();
+            let value = mass * r * (6. * mass * r - r.powi(2) - 9. * q2 + 3. * a.powi(2))
+                + 4. * q2 * (q2 - a.powi(2))
+                - 8. * a * root.powi(3);
+            let derivative = mass * (12. * mass * r - 3. * r.powi(2) - 9. * q2 + 3. * a.powi(2))
+                - 12. * a * mass * root;
+            r -= value / derivative;
+        }
+        r
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kerr_newman_isco_has_the_kerr_and_reissner_nordstrom_limits() {
+        for (spin, prograde) in [(0., true), (0.5, true), (0.9, true), (0.9, false)] {
+            let kerr = BlackHole::compute_isco_radius(&1., &spin, &0., prograde);
+            let spin = if prograde { spin } else { -spin };
+            let charged = BlackHole::compute_charged_isco_radius(&1., &spin, &0.);
+            assert!((charged - kerr).abs() < 1e-9, "{charged} != {kerr}");
+        }
+
+        // Extremal Reissner-Nordström, and a = Q = M / 2 where r = (2 + √3) M
+        let extremal = BlackHole::compute_isco_radius(&1., &0., &1., true);
+        assert!((extremal - 4.).abs() < 1e-9, "{extremal} != 4");
+        let kerr_newman = BlackHole::compute_isco_radius(&1., &0.5, &0.5, true);
+        let expected = 2. + 3f64.sqrt();
+        assert!(
+            (kerr_newman - expected).abs() < 1e-9,
+            "{kerr_newman} != {expected}"
+        );
+    }
+
+    #[test]
+    fn novikov_thorne_flux_vanishes_at_the_isco_and_peaks_at_9_55_m_without_spin() {
+        // Schwarzschild, M = 1: the closed form and the numerical integration for charged
+        // holes agree on the zero-torque inner edge and on the maximum of the flux
+        let disk = AccretionDisk::new(2., 0., 0., 6.);
+        let closed_form = |r: f64| AccretionDisk::novikov_thorne_brightness(1., 0., 6., r);
+        let numerical = |r: f64| disk.page_thorne_brightness(r);
+        let radii: Vec<f64> = (1..4000).map(|i| 6. + 0.005 * i as f64).collect();
+
+        for flux in [&closed_form as &dyn Fn(f64) -> f64, &numerical] {
+            assert_eq!(flux(6.), 0.);
+            assert!(flux(6. + 1e-6) < 1e-6 * flux(9.55));
+
+            let peak = radii
+                .iter()
+                .copied()
+                .max_by(|&r1, &r2| flux(r1).total_cmp(&flux(r2)))
+                .unwrap();
+            assert!((peak - 9.55).abs() < 0.02, "peak at {peak}, expected 9.55");
+        }
+    }
+
+    #[test]
+    fn thick_disk_column_has_the_optical_depth_of_the_thin_disk() {
+        // Straight down through the disk at r = 10 M: the Gaussian vertical profile integrates
+        // to the vertical optical depth, less the tails beyond DISK_VOLUME_EXTENT scale heights
+        let disk = AccretionDisk::new(2., 0., 0., 6.).with_thickness(0.05, 5.);
+        let extent = crate::DISK_VOLUME_EXTENT * disk.scale_height(disk.accretion_r_max());
+        let top = CartesianCoords3D::cartesian(10., 0., extent);
+        let bottom = CartesianCoords3D::cartesian(10., 0., -extent);
+
+        let samples = disk.sample_volume(top, bottom, 0., 0., 0);
+        assert!(!samples.is_empty());
+        let thick: f64 = samples
+            .iter()
+            .map(|sample| {
+                let (_, opacity) = disk.volume_emission(sample, 1.).unwrap();
+                -(1. - opacity).ln()
+            })
+            .sum();
+
+        let crossing = DiskCrossing::new(CartesianCoords3D::cartesian(10., 0., 0.), 0., 1., 0);
+        let (_, _, opacity) = disk.emission(&crossing, 1.).unwrap();
+        let thin = -(1. - opacity).ln();
+
+        // Fraction of a Gaussian within 3 standard deviations
+        let expected = 0.9973 * thin;
+        assert!(
+            (thick - expected).abs() < 1e-3 * expected,
+            "thick disk optical depth {thick}, expected {expected}"
+        );
+    }
 }
@@ -0,0 +1,226 @@
+use std::io;
+use std::path::PathBuf;
+
+use crate::scene::Camera;
+use crate::{BlackHole, CartesianCoords2D, CartesianCoords3D, CartesianCoords4D};
+use crate::{Kerr, Metric, Norm, Orbit, Particle, RayTrace, Scene};
+
+const USAGE: &str = "Usage:
+  black-hole-sim                 open the viewer
+  black-hole-sim orbit --position X,Y,Z --velocity VX,VY,VZ [--photon] [--spin A]
+                       [--charge Q] [--duration T] [--output FILE]
+      Integrates the orbit of a massive particle, or of light with --photon, and writes it to
+      FILE (orbit.csv by default, JSON for a .json file). Geometrised units with M = 1:
+      lengths and times in M, velocities in c relative to the zero angular momentum
+      observer, which is at rest unless the black hole spins.
+  black-hole-sim trace (--pixel X,Y [--size W,H] | --origin X,Y,Z --direction DX,DY,DZ)
+                       [--camera X,Y,Z] [--spin A] [--charge Q] [--output FILE]
+      Traces a single ray like the viewer does and writes every RKF45 step, with its error
+      estimate and rejected tries, to FILE (trace.csv by default, JSON for a .json file).
+      Pixels are counted from the top left of a screen of the given size, 800x600 by default,
+      seen from the camera looking at the black hole. The ray leaves the origin in the world
+      direction otherwise.";
+
+fn invalid_input(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, format!("{message}\n\n{USAGE}"))
+}
+
+fn parse_number(option: &str, value: &str) -> io::Result<f64> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| invalid_input(format!("{option} expects a number, got {value}")))
+}
+
+fn parse_numbers(option: &str, value: &str) -> io::Result<Vec<f64>> {
+    value
+        .split(',')
+        .map(|component| parse_number(option, component))
+        .collect()
+}
+
+fn parse_vector(option: &str, value: &str) -> io::Result<CartesianCoords3D> {
+    match parse_numbers(option, value)?[..] {
+        [x, y, z] => Ok(CartesianCoords3D::cartesian(x, y, z)),
+        _ => Err(invalid_input(format!(
+            "{option} expects three comma-separated numbers, got {value}"
+        ))),
+    }
+}
+
+fn parse_pair(option: &str, value: &str) -> io::Result<CartesianCoords2D> {
+    match parse_numbers(option, value)?[..] {
+        [x, y] => Ok(CartesianCoords2D::cartesian(x, y)),
+        _ => Err(invalid_input(format!(
+            "{option} expects two comma-separated numbers, got {value}"
+        ))),
+    }
+}
+
+// Runs the command line subcommand, the arguments don't include the program name
+pub fn run_command(args: &[String]) -> io::Result<()> {
+    match args.first().map(String::as_str) {
+        Some("orbit") => orbit_command(&args[1..]),
+        Some("trace") => trace_command(&args[1..]),
+        Some("help" | "--help" | "-h") => {
+            println!("{USAGE}");
+            Ok(())
+        }
+        Some(command) => Err(invalid_input(format!("Unknown command {command}"))),
+        None => Err(invalid_input(String::from("Missing command"))),
+    }
+}
+
+fn orbit_command(args: &[String]) -> io::Result<()> {
+    let mut position = None;
+    let mut velocity = None;
+    let mut particle = Particle::Massive;
+    let (mut spin, mut charge) = (0., 0.);
+    let mut duration = crate::ORBIT_COMMAND_DURATION;
+    let mut output = PathBuf::from("orbit.csv");
+
+    let mut args = args.iter();
+    while let Some(option) = args.next() {
+        if option == "--photon" {
+            particle = Particle::Massless;
+            continue;
+        }
+        let value = args
+            .next()
+            .ok_or_else(|| invalid_input(format!("Missing value after {option}")))?;
+        match option.as_str() {
+            "--position" => position = Some(parse_vector(option, value)?),
+            "--velocity" => velocity = Some(parse_vector(option, value)?),
+            "--spin" => spin = parse_number(option, value)?,
+            "--charge" => charge = parse_number(option, value)?,
+            "--duration" => duration = parse_number(option, value)?,
+            "--output" => output = PathBuf::from(value),
+            _ => return Err(invalid_input(format!("Unknown option {option}"))),
+        }
+    }
+    let position = position.ok_or_else(|| invalid_input(String::from("Missing --position")))?;
+    let velocity = velocity.ok_or_else(|| invalid_input(String::from("Missing --velocity")))?;
+    match particle {
+        Particle::Massless if velocity.norm() < crate::DIV_EPSILON => {
+            return Err(invalid_input(String::from(
+                "--photon needs a non-zero --velocity for the direction of the light",
+            )));
+        }
+        Particle::Massive if velocity.norm() > crate::MAX_OBSERVER_SPEED => {
+            return Err(invalid_input(format!(
+                "Massive particles move slower than {}c, got --velocity of norm {}",
+                crate::MAX_OBSERVER_SPEED,
+                velocity.norm()
+            )));
+        }
+        _ => {}
+    }
+
+    let origin = CartesianCoords4D::cartesian(0., 0., 0., 0.);
+    let black_hole = BlackHole::kerr_newman(origin, 1., spin, charge);
+    let orbit = Orbit::integrate::<_, Kerr>(black_hole, particle, position, velocity, duration);
+    orbit.export(&output)?;
+
+    let summary = orbit.summary();
+    let format =
+        |value: Option<f64>| value.map_or(String::from("-"), |value| format!("{value:.6}"));
+    println!(
+        "Wrote {} steps to {}",
+        orbit.points().len(),
+        output.display()
+    );
+    println!("End: {:?}", orbit.end());
+    println!("Periapsis: {}", format(summary.periapsis()));
+    println!("Apoapsis: {}", format(summary.apoapsis()));
+    println!("Radial period: {}", format(summary.radial_period()));
+    println!(
+        "Periapsis precession per orbit (rad): {}",
+        format(summary.precession())
+    );
+    println!(
+        "Weak-field Schwarzschild precession (rad): {}",
+        format(summary.weak_field_precession())
+    );
+    Ok(())
+}
+
+// Ray to trace, see trace_command
+enum TraceTarget {
+    Pixel(CartesianCoords2D, CartesianCoords2D), // pixel and screen size
+    Ray(CartesianCoords3D, CartesianCoords3D),   // origin and world direction
+}
+
+fn trace_command(args: &[String]) -> io::Result<()> {
+    let (mut pixel, mut origin, mut direction, mut camera) = (None, None, None, None);
+    let mut size =
+        CartesianCoords2D::cartesian(crate::TRACE_COMMAND_WIDTH, crate::TRACE_COMMAND_HEIGHT);
+    let (mut spin, mut charge) = (0., 0.);
+    let mut output = PathBuf::from("trace.csv");
+
+    let mut args = args.iter();
+    while let Some(option) = args.next() {
+        let value = args
+            .next()
+            .ok_or_else(|| invalid_input(format!("Missing value after {option}")))?;
+        match option.as_str() {
+            "--pixel" => pixel = Some(parse_pair(option, value)?),
+            "--size" => size = parse_pair(option, value)?,
+            "--origin" => origin = Some(parse_vector(option, value)?),
+            "--direction" => direction = Some(parse_vector(option, value)?),
+            "--camera" => camera = Some(parse_vector(option, value)?),
+            "--spin" => spin = parse_number(option, value)?,
+            "--charge" => charge = parse_number(option, value)?,
+            "--output" => output = PathBuf::from(value),
+            _ => return Err(invalid_input(format!("Unknown option {option}"))),
+        }
+    }
+    let target = match (pixel, origin, direction) {
+        (Some(pixel), None, None) => TraceTarget::Pixel(pixel, size),
+        (None, Some(origin), Some(direction)) => TraceTarget::Ray(origin, direction),
+        _ => {
+            return Err(invalid_input(String::from(
+                "Expected either --pixel or both --origin and --direction",
+            )));
+        }
+    };
+
+    let center = CartesianCoords4D::cartesian(0., 0., 0., 0.);
+    let black_hole = BlackHole::kerr_newman(center, 1., spin, charge);
+    let trace = trace_ray::<Kerr>(black_hole, camera, target);
+    trace.export(&output)?;
+
+    let record = trace.record();
+    println!(
+        "Wrote {} steps to {}",
+        trace.steps().len(),
+        output.display()
+    );
+    println!("Hit: {:?}", record.hit());
+    println!(
+        "Accepted steps: {}, rejected tries: {}",
+        record.steps(),
+        record.retries()
+    );
+    println!("Minimum radius: {:.6}", record.min_radius());
+    println!("Affine length: {:.6}", record.affine_length());
+    Ok(())
+}
+
+fn trace_ray<M: Metric + From<BlackHole>>(
+    black_hole: BlackHole,
+    camera: Option<CartesianCoords3D>,
+    target: TraceTarget,
+) -> RayTrace {
+    let mut scene: Scene<M> = Scene::new(
+        crate::SCENE_WIDTH_FACTOR,
+        crate::SCENE_HEIGHT_FACTOR,
+        black_hole,
+    );
+    if let Some(position) = camera {
+        scene.set_camera(Camera::new(position, black_hole.coords().position()));
+    }
+    match target {
+        TraceTarget::Pixel(pixel, size) => scene.trace_pixel(pixel.x(), pixel.y(), size),
+        TraceTarget::Ray(origin, direction) => scene.trace_ray(origin, direction),
+    }
+}
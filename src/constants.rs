@@ -2,6 +2,8 @@ use macroquad::color::{BLACK, Color};
 
 pub const SPEED_OF_LIGHT: f64 = 299_792_458.0; // m/s
 pub const GRAVITATIONAL_CONSTANT: f64 = 6.67430e-11;
+pub const PLANCK_CONSTANT: f64 = 6.62607015e-34; // J s
+pub const BOLTZMANN_CONSTANT: f64 = 1.380649e-23; // J/K
 pub const DIV_EPSILON: f64 = 1e-8;
 pub const CAMERA_THETA_EPSILON: f64 = 1e-3;
 pub const CAMERA_ROTATION_SENSITIVITY: f64 = 1.;
@@ -12,6 +14,7 @@ pub const SCENE_WIDTH_FACTOR: f64 = 200.;
 pub const SCENE_HEIGHT_FACTOR: f64 = 200.;
 pub const BOUNDING_BOX_FACTOR: f64 = 400.;
 pub const BLACK_HOLE_COLORED_SPHERE_RADIUS_FACTOR: f64 = 1.01; // needs to be > 1;
+pub const SINGULARITY_RADIUS_FACTOR: f64 = 0.05; // only used with horizon-penetrating metrics
 
 pub const INTEGRATION_STEP_FACTOR: f64 = 0.1;
 pub const NUM_INTEGRATION_STEPS: usize = 1000;
@@ -23,6 +26,79 @@ pub const RKF45_MAX_STEP_FACTOR: f64 = 1e2;
 pub const RKF45_MAX_STEP_RATIO: f64 = 5.0;
 pub const RKF45_RETRIES: usize = 20;
 
+// Novikov-Thorne flux of charged black holes, integrated numerically
+pub const NOVIKOV_THORNE_INTEGRATION_STEPS: usize = 200;
+pub const NOVIKOV_THORNE_DIFFERENCE_STEP: f64 = 1e-5; // relative to the radius
+
+// Thick accretion disk
+pub const DISK_VOLUME_EXTENT: f64 = 3.; // in scale heights
+pub const DISK_VOLUME_SAMPLES_PER_SCALE_HEIGHT: f64 = 4.;
+pub const DISK_VOLUME_MAX_SAMPLES: usize = 64; // per integration step
+pub const DISK_VOLUME_MAX_CHORD: f64 = 4.; // in scale heights at the ISCO
+
+// Procedural disk texture
+pub const DISK_TEXTURE_CELLS: u32 = 24; // noise cells per turn for the largest eddies
+pub const DISK_TEXTURE_OCTAVES: usize = 4;
+pub const FRAME_TIME_FACTOR: f64 = 1.; // proper time of the camera between two frames, in Schwarzschild radii
+pub const MAX_OBSERVER_SPEED: f64 = 0.999; // relative to the Static observer, in units of c
+pub const MAX_POLARISATION_FRACTION: f64 = 0.7; // optically thin synchrotron, across the field
+
+// Stars moving along timelike geodesics
+pub const STAR_RADIUS_FACTOR: f64 = 0.5;
+pub const STAR_TEMPERATURE: f64 = 28_000.; // K, a B-type main-sequence star like S2
+pub const STAR_HISTORY_FACTOR: f64 = 2.; // in bounding box radii of past coordinate time
+pub const STAR_STEP_FACTOR: f64 = 0.05; // proper time step, in units of the orbital radius
+pub const STAR_MAX_EVENTS: usize = 100_000;
+pub const STAR_INTERSECTION_ITERATIONS: usize = 2;
+
+// Analytic shadow edge
+pub const CRITICAL_CURVE_POINTS: usize = 720;
+pub const CRITICAL_CURVE_MIN_SPIN: f64 = 1e-4; // in units of M, below it the shadow is a circle
+pub const CRITICAL_CURVE_SCAN_STEPS: usize = 1000;
+pub const CRITICAL_CURVE_BISECTIONS: usize = 50;
+pub const CRITICAL_CURVE_COLOR: Color = Color::new(0., 1., 0., 1.);
+pub const CRITICAL_CURVE_THICKNESS: f32 = 1.;
+
+// Arbitrary output variables, in the order of HitKind: unfinished, horizon, escaped, disk,
+// disk volume, star
+pub const AOV_HIT_COLORS: [Color; 6] = [
+    Color::new(1., 0., 1., 1.),
+    Color::new(0.2, 0.2, 0.2, 1.),
+    Color::new(0.2, 0.4, 1., 1.),
+    Color::new(1., 0.6, 0., 1.),
+    Color::new(1., 0.9, 0.3, 1.),
+    Color::new(1., 1., 1., 1.),
+];
+pub const AOV_REDSHIFT_RANGE: f64 = 4.; // g at which the redshift map saturates
+
+// Orbit calculator
+pub const TRAJECTORY_STEP_FACTOR: f64 = 0.02; // affine step, in units of the radius
+pub const TRAJECTORY_MAX_STEPS: usize = 1_000_000;
+pub const TRAJECTORY_CIRCULAR_TOLERANCE: f64 = 1e-6; // relative radial oscillation
+pub const ORBIT_COMMAND_DURATION: f64 = 10_000.; // in M
+
+// Single-ray debug trace, pixels are taken on a screen the size of the default window
+pub const TRACE_COMMAND_WIDTH: f64 = 800.;
+pub const TRACE_COMMAND_HEIGHT: f64 = 600.;
+
+// Orbital-plane tracer, steps are angles in radians
+pub const ORBIT_INITIAL_STEP: f64 = 1e-2;
+pub const ORBIT_MIN_STEP: f64 = 1e-7;
+pub const ORBIT_MAX_STEP: f64 = 5e-2;
+
+// Deflection lookup table
+pub const DEFLECTION_TABLE_SIZE: usize = 4096;
+pub const DEFLECTION_TABLE_PSI_STEP: f64 = 1e-2; // radians
+
+// Blackbody colours, wavelengths in nm
+pub const SPECTRUM_MIN_WAVELENGTH: f64 = 380.;
+pub const SPECTRUM_MAX_WAVELENGTH: f64 = 780.;
+pub const SPECTRUM_WAVELENGTH_STEP: f64 = 5.;
+pub const SPECTRUM_BINS: usize = 40; // spectral render mode
+pub const BLACKBODY_MIN_TEMPERATURE: f64 = 500.; // K
+pub const BLACKBODY_MAX_TEMPERATURE: f64 = 500_000.; // K
+pub const BLACKBODY_TABLE_SIZE: usize = 1024;
+
 pub const BACKGROUND_COLOR: Color = BLACK;
 
 pub const NUM_THREADS: u32 = 24;
@@ -11,7 +11,7 @@ use crate::scene::Camera;
 use crate::{BLOCK_SIZE, Backend};
 use crate::{
     BlackHole, CUDAAccretionDisk, CUDABlackHole, CUDACamera, CUDAHyperparameters, CUDASkybox,
-    Hyperparameters, Scene, Skybox,
+    Hyperparameters, Metric, Scene, Skybox,
 };
 
 struct OutputBuffer {
@@ -86,13 +86,13 @@ impl Backend for CUDABackend {
         })
     }
 
-    fn compute(
+    fn compute<M: Metric>(
         &mut self,
         accretion_disk: &AccretionDisk,
         black_hole: &BlackHole,
         skybox: Arc<Skybox>,
         camera: &Camera,
-        scene: &Scene,
+        scene: &Scene<M>,
         hyperparams: &Hyperparameters,
     ) -> Result<Image, Box<dyn Error>> {
         let hyperparams: CUDAHyperparameters = hyperparams.into();
@@ -1,6 +1,7 @@
 #pragma once
 
 #include "color.cuh"
+#include "disk_texture.cuh"
 #include "tensor_ops.cuh"
 
 __device__ Color kelvin_to_rgb(float temp_kelvin) {
@@ -40,23 +41,40 @@ struct AccretionDisk {
     double doppler_factor;
     double fade_start_ratio;
     double peak_brightness;
+    double mass;
+    double spin;
+    double charge;
+    double time; // time of the scene, the texture rotates with it
+    DiskTexture texture;
 
     __device__ AccretionDisk()
         : r_isco(0.0), accretion_r_max(0.0), width(0.0), max_temperature(0.0),
           step_opacity(0.0), doppler_factor(0.0), fade_start_ratio(0.0),
-          peak_brightness(0.0) {}
+          peak_brightness(0.0), mass(0.0), spin(0.0), charge(0.0), time(0.0),
+          texture() {}
 
     __device__ AccretionDisk(double r_isco, double accretion_r_max,
                              double width, double max_temperature,
                              double step_opacity, double doppler_factor,
-                             double fade_start_ratio, double peak_brightness)
+                             double fade_start_ratio, double peak_brightness,
+                             double mass, double spin, double charge,
+                             double time, DiskTexture texture)
         : r_isco(r_isco), accretion_r_max(accretion_r_max), width(width),
           max_temperature(max_temperature), step_opacity(step_opacity),
           doppler_factor(doppler_factor), fade_start_ratio(fade_start_ratio),
-          peak_brightness(peak_brightness) {}
+          peak_brightness(peak_brightness), mass(mass), spin(spin),
+          charge(charge), time(time), texture(texture) {}
 
-    __device__ double check_intersection(float3 position1,
-                                         float3 position2) const {
+    // Keplerian angular velocity of the gas, see AccretionDisk::angular_velocity
+    __device__ double angular_velocity(double radius) const {
+        double root = __dsqrt_rn(fmax(mass * radius - charge * charge, 0.0));
+        return root / (radius * radius + spin * root);
+    }
+
+    // Radius of the crossing, or -1 if there is none. The azimuth of the
+    // crossing is written to azimuth.
+    __device__ double check_intersection(float3 position1, float3 position2,
+                                         double &azimuth) const {
         float dz = position2.z - position1.z;
         if (fabs(dz) < 1e-10) return -1.0;
 
@@ -67,14 +85,22 @@ struct AccretionDisk {
         double r_plane = (double)length(equator_collision);
         if (r_plane < r_isco || r_plane > accretion_r_max) return -1;
 
+        azimuth = atan2((double)equator_collision.y,
+                        (double)equator_collision.x);
         return r_plane;
     }
 
-    __device__ Color get_color(double radius) const {
+    __device__ Color get_color(double radius, double azimuth) const {
         if (radius < r_isco || radius > accretion_r_max) return Color();
 
+        // Each ring has turned by Ω t since the texture was laid down. Light
+        // travel time isn't integrated, the disk is seen as it is at the
+        // time of the scene.
+        double texture_factor =
+            texture.sample(radius, azimuth - angular_velocity(radius) * time);
         double normalized_brightness =
-            min(brightness(r_isco, radius) / peak_brightness, 1.0);
+            min(brightness(r_isco, radius) / peak_brightness, 1.0) *
+            texture_factor;
         double normalized_radius = (radius - r_isco) / width;
         double geometric_falloff =
             1.0 - smoothstep(fade_start_ratio, 1.0, normalized_radius);
@@ -0,0 +1,91 @@
+#pragma once
+
+#include "tensor_ops.cuh"
+
+// Same texture as DiskTexture on the CPU, sample for sample
+
+const double TEXTURE_TAU = 6.283185307179586;
+
+// Integer hash of a lattice point, uniform in [0, 1)
+__device__ double texture_hash(unsigned int seed, long long i, long long j) {
+    unsigned long long h =
+        (unsigned long long)seed ^
+        ((unsigned long long)i * 0x9E3779B97F4A7C15ull);
+    h ^= (unsigned long long)j * 0xC2B2AE3D27D4EB4Full;
+    h ^= h >> 33;
+    h *= 0xFF51AFD7ED558CCDull;
+    h ^= h >> 33;
+    h *= 0xC4CEB9FE1A85EC53ull;
+    h ^= h >> 33;
+    return (double)(h >> 11) / (double)(1ull << 53);
+}
+
+// Smoothly interpolated lattice noise in [0, 1], periodic in v with the given period
+__device__ double value_noise(unsigned int seed, double u, double v,
+                              long long period) {
+    long long i = (long long)floor(u);
+    long long j = (long long)floor(v);
+    double fu = u - (double)i;
+    double fv = v - (double)j;
+    double su = fu * fu * (3.0 - 2.0 * fu);
+    double sv = fv * fv * (3.0 - 2.0 * fv);
+
+    long long j0 = ((j % period) + period) % period;
+    long long j1 = (((j + 1) % period) + period) % period;
+    double c00 = texture_hash(seed, i, j0);
+    double c10 = texture_hash(seed, i + 1, j0);
+    double c01 = texture_hash(seed, i, j1);
+    double c11 = texture_hash(seed, i + 1, j1);
+    double bottom = c00 + su * (c10 - c00);
+    double top = c01 + su * (c11 - c01);
+    return bottom + sv * (top - bottom);
+}
+
+struct DiskTexture {
+    unsigned int seed;
+    double contrast; // 0 for a smooth disk, or when the disk has no texture
+    unsigned int spiral_arms;
+    double pitch_angle;
+    double spiral_weight;
+    unsigned int cells;
+    unsigned int octaves;
+
+    __device__ double turbulence(double log_radius, double azimuth) const {
+        long long num_cells = cells;
+        double amplitude = 1.0;
+        double sum = 0.0;
+        double total = 0.0;
+        double wrapped_azimuth = fmod(azimuth, TEXTURE_TAU);
+        if (wrapped_azimuth < 0.0) wrapped_azimuth += TEXTURE_TAU;
+        for (unsigned int octave = 0; octave < octaves; ++octave) {
+            double scale = (double)num_cells / TEXTURE_TAU;
+            double noise = value_noise(seed + octave, log_radius * scale,
+                                       wrapped_azimuth * scale, num_cells);
+            sum += amplitude * noise;
+            total += amplitude;
+            num_cells *= 2;
+            amplitude *= 0.5;
+        }
+        return sum / total;
+    }
+
+    // Flux factor at the given radius and azimuth, both measured in the frame
+    // of the gas
+    __device__ double sample(double radius, double azimuth) const {
+        if (contrast == 0.0) return 1.0;
+
+        double log_radius = log(fmax(radius, 1e-10));
+        double noise = turbulence(log_radius, azimuth);
+
+        double pattern = noise;
+        if (spiral_arms > 0) {
+            double phase =
+                spiral_arms * (azimuth - log_radius / tan(pitch_angle)) +
+                TEXTURE_TAU * noise;
+            double spiral = 0.5 + 0.5 * cos(phase);
+            pattern = (1.0 - spiral_weight) * noise + spiral_weight * spiral;
+        }
+
+        return fmax(1.0 + contrast * (2.0 * pattern - 1.0), 0.0);
+    }
+};
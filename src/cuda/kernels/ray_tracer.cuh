@@ -19,21 +19,25 @@ struct StoppingResult {
     StoppingCriterion criterion;
     float3 direction;
     double radius;
+    double azimuth;
 
     __device__ StoppingResult(StoppingCriterion criterion, float3 direction,
                               double radius)
-        : criterion(criterion), direction(direction), radius(radius) {}
+        : criterion(criterion), direction(direction), radius(radius),
+          azimuth(0.) {}
 
     __device__ StoppingResult(StoppingCriterion criterion, float3 direction)
-        : criterion(criterion), direction(direction), radius(0.) {}
+        : criterion(criterion), direction(direction), radius(0.), azimuth(0.) {
+    }
 
-    __device__ StoppingResult(StoppingCriterion criterion, double radius)
+    __device__ StoppingResult(StoppingCriterion criterion, double radius,
+                              double azimuth)
         : criterion(criterion), direction(make_float3(0., 0., 0.)),
-          radius(radius) {}
+          radius(radius), azimuth(azimuth) {}
 
     __device__ StoppingResult(StoppingCriterion criterion)
-        : criterion(criterion), direction(make_float3(0., 0., 0.)), radius(0.) {
-    }
+        : criterion(criterion), direction(make_float3(0., 0., 0.)), radius(0.),
+          azimuth(0.) {}
 
     __device__ Color determine_color(const BlackHole &black_hole,
                                      const AccretionDisk &accretion_disk,
@@ -42,8 +46,7 @@ struct StoppingResult {
         case StoppingCriterion::ENTERED_EVENT_HORIZON:
             return black_hole.get_color();
         case StoppingCriterion::CROSSED_ACCRETION_DISK:
-            Color color = accretion_disk.get_color(radius);
-            return accretion_disk.get_color(radius);
+            return accretion_disk.get_color(radius, azimuth);
         case StoppingCriterion::OUT_OF_BOUNDING_BOX:
             return skybox.get_color(direction);
         // We should never reach this state or default, this is only a security.
@@ -98,12 +101,14 @@ __device__ StepResult step(const Ray &ray, const BlackHole &black_hole,
     }
 
     // Check if we crossed accretion disk
+    double azimuth = 0.;
     double disk_intersection = accretion_disk.check_intersection(
         position_to_cartesian(ray.spatial_position()),
-        position_to_cartesian(rk_result.state.spatial_position()));
+        position_to_cartesian(rk_result.state.spatial_position()), azimuth);
     if (disk_intersection != -1) {
-        return StepResult(StoppingResult(
-            StoppingCriterion::CROSSED_ACCRETION_DISK, disk_intersection));
+        return StepResult(
+            StoppingResult(StoppingCriterion::CROSSED_ACCRETION_DISK,
+                           disk_intersection, azimuth));
     }
 
     double new_r = rk_result.state.position.x;
@@ -1,6 +1,9 @@
 use cudarc::driver::{CudaSlice, DeviceRepr};
 
-use crate::{BlackHole, Hyperparameters, Scene, Skybox, black_hole::AccretionDisk, scene::Camera};
+use crate::{
+    BlackHole, DiskTexture, Hyperparameters, Metric, Scene, Skybox, black_hole::AccretionDisk,
+    scene::Camera,
+};
 
 #[repr(C)]
 pub struct CUDABlackHole {
@@ -32,6 +35,11 @@ pub struct CUDAAccretionDisk {
     pub doppler_factor: f64,
     pub fade_start_ratio: f64,
     pub peak_brigthness: f64,
+    pub mass: f64,
+    pub spin: f64,
+    pub charge: f64,
+    pub time: f64,
+    pub texture: CUDADiskTexture,
 }
 
 impl From<&AccretionDisk> for CUDAAccretionDisk {
@@ -45,12 +53,46 @@ impl From<&AccretionDisk> for CUDAAccretionDisk {
             doppler_factor: value.doppler_factor(),
             fade_start_ratio: value.fade_start_ratio(),
             peak_brigthness: value.peak_brigthness(),
+            mass: value.mass(),
+            spin: value.spin(),
+            charge: value.charge(),
+            time: value.time(),
+            texture: value.texture().into(),
         }
     }
 }
 
 unsafe impl DeviceRepr for CUDAAccretionDisk {}
 
+#[repr(C)]
+pub struct CUDADiskTexture {
+    pub seed: u32,
+    pub contrast: f64,
+    pub spiral_arms: u32,
+    pub pitch_angle: f64,
+    pub spiral_weight: f64,
+    pub cells: u32,
+    pub octaves: u32,
+}
+
+impl From<Option<DiskTexture>> for CUDADiskTexture {
+    fn from(value: Option<DiskTexture>) -> Self {
+        // A disk without texture is a texture without contrast
+        let texture = value.unwrap_or(DiskTexture::new(0).with_contrast(0.));
+        Self {
+            seed: texture.seed(),
+            contrast: texture.contrast(),
+            spiral_arms: texture.spiral_arms(),
+            pitch_angle: texture.pitch_angle(),
+            spiral_weight: texture.spiral_weight(),
+            cells: crate::DISK_TEXTURE_CELLS,
+            octaves: crate::DISK_TEXTURE_OCTAVES as u32,
+        }
+    }
+}
+
+unsafe impl DeviceRepr for CUDADiskTexture {}
+
 #[repr(C)]
 pub struct CUDACamera {
     pub position: [f32; 3],
@@ -64,7 +106,7 @@ pub struct CUDACamera {
 }
 
 impl CUDACamera {
-    pub fn from_camera_scene(camera: &Camera, scene: &Scene) -> Self {
+    pub fn from_camera_scene<M: Metric>(camera: &Camera, scene: &Scene<M>) -> Self {
         let position = {
             let coords = camera.position().unpack_as_f32();
             [coords.0, coords.1, coords.2]
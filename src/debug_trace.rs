@@ -0,0 +1,224 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+
+use macroquad::prelude::Color;
+
+use crate::orbit::json_number;
+use crate::{BlackHole, Metric, Ray, RayRecord, Skybox, SphericalState4D};
+
+// Size and error estimate of one RKF45 try
+#[derive(Debug, Clone, Copy)]
+pub struct StepTry {
+    dλ: f64,
+    error: f64,
+}
+
+impl StepTry {
+    pub fn new(dλ: f64, error: f64) -> Self {
+        Self { dλ, error }
+    }
+
+    pub fn dλ(&self) -> f64 {
+        self.dλ
+    }
+    pub fn error(&self) -> f64 {
+        self.error
+    }
+}
+
+// One RKF45 step of a ray: the state it reached with the size and error estimate of the
+// accepted try, and the tries rejected before it. A failed step keeps the state it started
+// from, its last try being rejected as well.
+#[derive(Debug, Clone)]
+pub struct TraceStep<S = SphericalState4D> {
+    state: S,
+    dλ: f64,
+    error: f64,
+    rejected: Vec<StepTry>,
+    accepted: bool,
+}
+
+impl<S: Copy> TraceStep<S> {
+    // None without any try
+    pub(crate) fn new(state: S, mut tries: Vec<StepTry>, accepted: bool) -> Option<Self> {
+        let last = tries.pop()?;
+        Some(Self {
+            state,
+            dλ: last.dλ,
+            error: last.error,
+            rejected: tries,
+            accepted,
+        })
+    }
+
+    pub fn state(&self) -> S {
+        self.state
+    }
+    pub fn dλ(&self) -> f64 {
+        self.dλ
+    }
+    pub fn error(&self) -> f64 {
+        self.error
+    }
+    pub fn rejected(&self) -> &[StepTry] {
+        &self.rejected
+    }
+    pub fn accepted(&self) -> bool {
+        self.accepted
+    }
+}
+
+// Every step of a single ray traced like the pixels of get_image, from the camera to where it
+// stopped, to look into the path of a pixel that looks wrong
+#[derive(Debug, Clone)]
+pub struct RayTrace {
+    initial_state: SphericalState4D,
+    steps: Vec<TraceStep>,
+    color: Color,
+    record: RayRecord,
+}
+
+impl RayTrace {
+    pub fn new<M: Metric>(ray: Ray<M>, black_hole: BlackHole, skybox: Arc<Skybox>) -> Self {
+        let mut ray = ray.with_step_log();
+        let initial_state = ray.state();
+        let bounding_box_radius = black_hole.radius() * crate::BOUNDING_BOX_FACTOR;
+        let (color, record) = ray.get_color_with_record(black_hole, bounding_box_radius, skybox);
+        Self {
+            initial_state,
+            steps: ray.step_log().to_vec(),
+            color,
+            record,
+        }
+    }
+
+    pub fn initial_state(&self) -> SphericalState4D {
+        self.initial_state
+    }
+    pub fn steps(&self) -> &[TraceStep] {
+        &self.steps
+    }
+    pub fn color(&self) -> Color {
+        self.color
+    }
+    pub fn record(&self) -> RayRecord {
+        self.record
+    }
+
+    // One row per step, the first one being the state at the camera. Rejected tries are
+    // listed as dλ:error pairs separated by semicolons.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from(
+            "step,accepted,affine_parameter,t,r,theta,phi,dt,dr,dtheta,dphi,dlambda,error,rejected\n",
+        );
+        let row = |step: usize, accepted: bool, affine_parameter: f64, state: SphericalState4D| {
+            format!(
+                "{step},{accepted},{affine_parameter},{},{},{},{},{},{},{},{}",
+                state.t(),
+                state.r(),
+                state.theta(),
+                state.phi(),
+                state.dt(),
+                state.dr(),
+                state.dtheta(),
+                state.dphi(),
+            )
+        };
+        csv.push_str(&row(0, true, 0., self.initial_state));
+        csv.push_str(",,,\n");
+        let mut affine_parameter = 0.;
+        for (index, step) in self.steps.iter().enumerate() {
+            if step.accepted {
+                affine_parameter += step.dλ;
+            }
+            let rejected: Vec<String> = step
+                .rejected
+                .iter()
+                .map(|attempt| format!("{}:{}", attempt.dλ, attempt.error))
+                .collect();
+            csv.push_str(&row(index + 1, step.accepted, affine_parameter, step.state));
+            csv.push_str(&format!(
+                ",{},{},{}\n",
+                step.dλ,
+                step.error,
+                rejected.join(";")
+            ));
+        }
+        csv
+    }
+
+    pub fn to_json(&self) -> String {
+        let state = |state: SphericalState4D| {
+            format!(
+                "{{\"t\":{},\"r\":{},\"theta\":{},\"phi\":{},\"dt\":{},\"dr\":{},\"dtheta\":{},\"dphi\":{}}}",
+                json_number(Some(state.t())),
+                json_number(Some(state.r())),
+                json_number(Some(state.theta())),
+                json_number(Some(state.phi())),
+                json_number(Some(state.dt())),
+                json_number(Some(state.dr())),
+                json_number(Some(state.dtheta())),
+                json_number(Some(state.dphi())),
+            )
+        };
+        let attempt = |attempt: &StepTry| {
+            format!(
+                "{{\"dlambda\":{},\"error\":{}}}",
+                json_number(Some(attempt.dλ)),
+                json_number(Some(attempt.error)),
+            )
+        };
+        let steps: Vec<String> = self
+            .steps
+            .iter()
+            .map(|step| {
+                let rejected: Vec<String> = step.rejected.iter().map(attempt).collect();
+                format!(
+                    "{{\"accepted\":{},\"state\":{},\"dlambda\":{},\"error\":{},\"rejected\":[{}]}}",
+                    step.accepted,
+                    state(step.state),
+                    json_number(Some(step.dλ)),
+                    json_number(Some(step.error)),
+                    rejected.join(","),
+                )
+            })
+            .collect();
+        let record = &self.record;
+        let escape_direction = record.escape_direction().map_or(String::from("null"), |d| {
+            format!(
+                "[{},{},{}]",
+                json_number(Some(d.x())),
+                json_number(Some(d.y())),
+                json_number(Some(d.z())),
+            )
+        });
+        format!(
+            "{{\"hit\":\"{:?}\",\"color\":[{},{},{},{}],\"steps\":{},\"retries\":{},\"min_radius\":{},\"affine_length\":{},\"escape_direction\":{},\"disk_radius\":{},\"redshift\":{},\"initial_state\":{},\"trace\":[\n{}\n]}}\n",
+            record.hit(),
+            self.color.r,
+            self.color.g,
+            self.color.b,
+            self.color.a,
+            record.steps(),
+            record.retries(),
+            json_number(Some(record.min_radius())),
+            json_number(Some(record.affine_length())),
+            escape_direction,
+            json_number(record.disk_radius()),
+            json_number(record.redshift()),
+            state(self.initial_state),
+            steps.join(",\n"),
+        )
+    }
+
+    // Writes to_json for a .json path and to_csv otherwise
+    pub fn export(&self, path: &Path) -> io::Result<()> {
+        let contents = match path.extension().and_then(|extension| extension.to_str()) {
+            Some("json") => self.to_json(),
+            _ => self.to_csv(),
+        };
+        fs::write(path, contents)
+    }
+}
@@ -0,0 +1,355 @@
+use macroquad::prelude::*;
+use std::f64::consts::PI;
+use std::sync::Arc;
+use std::thread;
+
+use crate::black_hole::DiskCrossing;
+use crate::orbital_plane::orbital_plane_basis;
+use crate::ray::{Photon, StoppingCriterion, blend, determine_color, gamma_correct};
+use crate::{BlackHole, CartesianCoords3D, CartesianState3D, Metric, Norm};
+use crate::{Schwarzschild, Skybox, SphericalState4D};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Fate {
+    Captured,
+    Escaped,
+    // Ran out of integration steps, the tracer only blends the background color
+    Unresolved,
+}
+
+struct DeflectionEntry {
+    fate: Fate,
+    // Angle swept in the orbital plane when the ray is captured or leaves the bounding sphere
+    deflection: f64,
+    // w = rs / r sampled every DEFLECTION_TABLE_PSI_STEP radians along the orbital plane
+    w: Vec<f32>,
+}
+
+impl DeflectionEntry {
+    // Integrates the equatorial ray with impact parameter b starting at the camera radius
+    fn trace(black_hole: BlackHole, camera_radius: f64, b: f64, ingoing: bool, dλ0: f64) -> Self {
+        let rs = black_hole.radius();
+        let metric = Schwarzschild::new(rs);
+        let bounding_box_radius = rs * crate::BOUNDING_BOX_FACTOR;
+
+        // E = 1 and L = b, traced backwards in time like Ray
+        let f = 1. - rs / camera_radius;
+        let radial_velocity = (1. - f * b * b / camera_radius.powi(2)).max(0.).sqrt();
+        let dr = if ingoing {
+            -radial_velocity
+        } else {
+            radial_velocity
+        };
+        let mut state = SphericalState4D::spherical(
+            0.,
+            camera_radius,
+            PI / 2.,
+            0.,
+            -1. / f,
+            dr,
+            0.,
+            b / camera_radius.powi(2),
+        );
+        let mut dλ = dλ0;
+
+        let mut samples = vec![(0., rs / camera_radius)];
+        let mut fate = Fate::Unresolved;
+
+        for i in 0..crate::NUM_INTEGRATION_STEPS {
+            if i > 0 && i % crate::NORMALIZATION_INTERVAL == 0 {
+                state = metric.renormalize(state);
+            }
+
+            if state.r() <= black_hole.visual_radius() {
+                fate = Fate::Captured;
+                break;
+            }
+
+            match crate::geodesic::solve_geodesic_rkf45(state, &metric, rs, dλ) {
+                Ok((next_state, next_dλ)) => {
+                    state = next_state;
+                    dλ = next_dλ;
+                    samples.push((state.phi(), rs / state.r()));
+                }
+                Err(_) => {
+                    // Same fallback as Ray::step
+                    fate = if state.r() < black_hole.visual_radius() {
+                        Fate::Captured
+                    } else {
+                        Fate::Escaped
+                    };
+                    break;
+                }
+            }
+
+            if state.r() > bounding_box_radius && state.dr() > 0. {
+                fate = Fate::Escaped;
+                break;
+            }
+        }
+
+        let deflection = state.phi();
+        Self {
+            fate,
+            deflection,
+            w: Self::resample(&samples, deflection),
+        }
+    }
+
+    // Linear interpolation of the (ψ, w) samples on a uniform ψ grid covering [0, deflection]
+    fn resample(samples: &[(f64, f64)], deflection: f64) -> Vec<f32> {
+        let num_samples = (deflection / crate::DEFLECTION_TABLE_PSI_STEP).ceil() as usize + 1;
+        let mut w = Vec::with_capacity(num_samples);
+        let mut segment = 0;
+
+        for k in 0..num_samples {
+            let psi = k as f64 * crate::DEFLECTION_TABLE_PSI_STEP;
+            while segment + 2 < samples.len() && samples[segment + 1].0 < psi {
+                segment += 1;
+            }
+            let value = match (samples.get(segment), samples.get(segment + 1)) {
+                (Some(&(psi0, w0)), Some(&(psi1, w1))) if psi1 > psi0 => {
+                    let fraction = ((psi - psi0) / (psi1 - psi0)).clamp(0., 1.);
+                    w0 + fraction * (w1 - w0)
+                }
+                (Some(&(_, w0)), _) => w0,
+                (None, _) => 0.,
+            };
+            w.push(value as f32);
+        }
+        w
+    }
+
+    fn w_at(&self, psi: f64) -> f64 {
+        let index = (psi / crate::DEFLECTION_TABLE_PSI_STEP).max(0.);
+        let i = index as usize;
+        match (self.w.get(i), self.w.get(i + 1)) {
+            (Some(&w0), Some(&w1)) => {
+                let fraction = index - i as f64;
+                w0 as f64 + fraction * (w1 - w0) as f64
+            }
+            _ => self.w.last().copied().unwrap_or(0.) as f64,
+        }
+    }
+}
+
+// Fate of a ray interpolated between two neighbouring entries of the table
+struct Lookup<'a> {
+    lower: &'a DeflectionEntry,
+    upper: &'a DeflectionEntry,
+    fraction: f64,
+}
+
+impl Lookup<'_> {
+    fn fate(&self) -> Fate {
+        self.lower.fate
+    }
+
+    fn deflection(&self) -> f64 {
+        self.lower.deflection + self.fraction * (self.upper.deflection - self.lower.deflection)
+    }
+
+    fn w_at(&self, psi: f64) -> f64 {
+        let w0 = self.lower.w_at(psi);
+        w0 + self.fraction * (self.upper.w_at(psi) - w0)
+    }
+}
+
+// For a static Schwarzschild camera the fate of a ray only depends on its impact parameter:
+// it stays in the plane spanned by the camera position and its direction, so its trajectory
+// is a rotation of an equatorial ray with the same impact parameter. The table integrates
+// that 1D family once so that rendering a frame only needs lookups.
+// Only valid for non-rotating, uncharged black holes, a thin disk and a fixed camera radius.
+pub struct DeflectionTable {
+    rs: f64,
+    camera_radius: f64,
+    impact_parameter_step: f64,
+    // Rays leaving the camera towards / away from the black hole, uniformly sampled in impact parameter
+    ingoing: Vec<DeflectionEntry>,
+    outgoing: Vec<DeflectionEntry>,
+}
+
+impl DeflectionTable {
+    pub fn new(black_hole: BlackHole, camera_radius: f64, dλ0: f64) -> Self {
+        let rs = black_hole.radius();
+        // Largest impact parameter reachable from the camera, for a purely tangential ray
+        let max_impact_parameter = camera_radius / (1. - rs / camera_radius).sqrt();
+        let impact_parameter_step =
+            max_impact_parameter / (crate::DEFLECTION_TABLE_SIZE - 1) as f64;
+
+        let trace_family = |ingoing: bool| {
+            let impact_parameters: Vec<f64> = (0..crate::DEFLECTION_TABLE_SIZE)
+                .map(|i| i as f64 * impact_parameter_step)
+                .collect();
+            let chunk_size = impact_parameters
+                .len()
+                .div_ceil(crate::NUM_THREADS as usize);
+
+            thread::scope(|scope| {
+                let handles: Vec<_> = impact_parameters
+                    .chunks(chunk_size)
+                    .map(|chunk| {
+                        scope.spawn(move || {
+                            chunk
+                                .iter()
+                                .map(|&b| {
+                                    DeflectionEntry::trace(
+                                        black_hole,
+                                        camera_radius,
+                                        b,
+                                        ingoing,
+                                        dλ0,
+                                    )
+                                })
+                                .collect::<Vec<_>>()
+                        })
+                    })
+                    .collect();
+                handles
+                    .into_iter()
+                    .flat_map(|handle| handle.join().unwrap())
+                    .collect()
+            })
+        };
+
+        Self {
+            rs,
+            camera_radius,
+            impact_parameter_step,
+            ingoing: trace_family(true),
+            outgoing: trace_family(false),
+        }
+    }
+
+    pub fn camera_radius(&self) -> f64 {
+        self.camera_radius
+    }
+
+    fn lookup(&self, impact_parameter: f64, ingoing: bool) -> Lookup<'_> {
+        let entries = if ingoing {
+            &self.ingoing
+        } else {
+            &self.outgoing
+        };
+        let index =
+            (impact_parameter / self.impact_parameter_step).clamp(0., (entries.len() - 1) as f64);
+        let i = (index as usize).min(entries.len() - 2);
+        let fraction = index - i as f64;
+
+        let (lower, upper) = (&entries[i], &entries[i + 1]);
+        if lower.fate != upper.fate {
+            // Don't blend across the edge of the shadow, use the closest ray instead
+            let closest = if fraction < 0.5 { lower } else { upper };
+            return Lookup {
+                lower: closest,
+                upper: closest,
+                fraction: 0.,
+            };
+        }
+        Lookup {
+            lower,
+            upper,
+            fraction,
+        }
+    }
+
+    pub fn get_color(
+        &self,
+        spatial_state: CartesianState3D,
+        black_hole: BlackHole,
+        skybox: Arc<Skybox>,
+    ) -> Color {
+        let position = spatial_state.position();
+        let direction = CartesianCoords3D::cartesian(
+            spatial_state.dx(),
+            spatial_state.dy(),
+            spatial_state.dz(),
+        );
+        let (e1, e2) = orbital_plane_basis(position, direction);
+
+        // Impact parameter L / E of the photon shot by the tracer in that direction
+        let state: SphericalState4D = Schwarzschild::new(self.rs).null_state(spatial_state);
+        let r = state.r();
+        let angular_velocity =
+            (state.dtheta().powi(2) + (state.theta().sin() * state.dphi()).powi(2)).sqrt();
+        let impact_parameter = r * r * angular_velocity / ((1. - self.rs / r) * state.dt().abs());
+
+        // The photon reaching the camera moves along -e2
+        let camera_energy = 1. / (1. - self.rs / r).sqrt();
+        let photon = Photon::new(
+            1. / camera_energy,
+            -impact_parameter * e1.cross(e2).z() / camera_energy,
+        );
+
+        let ray = self.lookup(impact_parameter, state.dr() < 0.);
+        let deflection = ray.deflection();
+
+        let mut accumulated_color = Color::new(0.0, 0.0, 0.0, 0.0);
+        let mut transmittance = 1.0;
+
+        // The orbital plane meets the disk plane z = 0 along a line, which the ray crosses every π radians.
+        // The table doesn't keep coordinate time, the disk is seen as it is at the camera time.
+        let disk = black_hole.accretion_disk();
+        if e1.z().abs() > crate::DIV_EPSILON || e2.z().abs() > crate::DIV_EPSILON {
+            let mut psi = (-e1.z()).atan2(e2.z()).rem_euclid(PI);
+            if psi == 0. {
+                psi = PI;
+            }
+            let mut order = 0;
+            while psi < deflection && transmittance >= 0.05 {
+                let w = ray.w_at(psi).max(crate::DIV_EPSILON);
+                let r = self.rs / w;
+                if r >= disk.r_isco() && r <= disk.accretion_r_max() {
+                    // Direction of the ray from dr/dψ = -r (dw/dψ) / w
+                    let step = crate::DEFLECTION_TABLE_PSI_STEP;
+                    let dw = (ray.w_at(psi + step) - ray.w_at((psi - step).max(0.))) / (2. * step);
+                    let (sin_psi, cos_psi) = psi.sin_cos();
+                    let radial = e1 * cos_psi + e2 * sin_psi;
+                    let tangential = e2 * cos_psi - e1 * sin_psi;
+                    let direction = radial * (-r * dw / w) + tangential * r;
+                    let cos_incidence = direction.z().abs() / direction.norm();
+
+                    let crossing = DiskCrossing::new(radial * r, 0., cos_incidence, order);
+                    let criterion = StoppingCriterion::CrossedAccretionDisk(crossing, photon);
+                    (accumulated_color, transmittance) = blend(
+                        accumulated_color,
+                        determine_color(&criterion, black_hole, &skybox),
+                        transmittance,
+                    );
+                }
+                psi += PI;
+                order += 1;
+            }
+        }
+
+        let criterion = match ray.fate() {
+            Fate::Captured => Some(StoppingCriterion::EnteredEventHorizon),
+            Fate::Escaped => {
+                let (sin_psi, cos_psi) = deflection.sin_cos();
+                Some(StoppingCriterion::OutOfBoundingBox(
+                    (e1 * cos_psi + e2 * sin_psi) * r,
+                ))
+            }
+            Fate::Unresolved => None,
+        };
+
+        if let Some(criterion) = criterion
+            && transmittance >= 0.05
+        {
+            // Ray::step doesn't move past the horizon or the bounding sphere, so Ray::get_color
+            // keeps blending them until the ray becomes opaque. Do the same to get matching images.
+            let hit_color = determine_color(&criterion, black_hole, &skybox);
+            for _ in 0..crate::NUM_INTEGRATION_STEPS {
+                (accumulated_color, transmittance) =
+                    blend(accumulated_color, hit_color, transmittance);
+                if transmittance < 0.05 {
+                    break;
+                }
+            }
+        }
+
+        let (final_color, _) = blend(accumulated_color, crate::BACKGROUND_COLOR, transmittance);
+
+        gamma_correct(final_color)
+    }
+}
@@ -0,0 +1,119 @@
+use std::f64::consts::PI;
+
+// Integer hash of a lattice point, uniform in [0, 1)
+fn hash(seed: u32, i: i64, j: i64) -> f64 {
+    let mut h = (seed as u64) ^ (i as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+    h ^= (j as u64).wrapping_mul(0xC2B2_AE3D_27D4_EB4F);
+    h ^= h >> 33;
+    h = h.wrapping_mul(0xFF51_AFD7_ED55_8CCD);
+    h ^= h >> 33;
+    h = h.wrapping_mul(0xC4CE_B9FE_1A85_EC53);
+    h ^= h >> 33;
+    (h >> 11) as f64 / (1u64 << 53) as f64
+}
+
+// Smoothly interpolated lattice noise in [0, 1], periodic in v with the given period
+fn value_noise(seed: u32, u: f64, v: f64, period: i64) -> f64 {
+    let (i, j) = (u.floor() as i64, v.floor() as i64);
+    let (fu, fv) = (u - i as f64, v - j as f64);
+    let (su, sv) = (fu * fu * (3. - 2. * fu), fv * fv * (3. - 2. * fv));
+
+    let corner = |di: i64, dj: i64| hash(seed, i + di, (j + dj).rem_euclid(period));
+    let bottom = corner(0, 0) + su * (corner(1, 0) - corner(0, 0));
+    let top = corner(0, 1) + su * (corner(1, 1) - corner(0, 1));
+    bottom + sv * (top - bottom)
+}
+
+// Procedural structure of the disk gas in its co-rotating frame: turbulence from seeded
+// value noise in (ln r, φ), so that eddies grow with the radius, and logarithmic spiral
+// filaments. The texture scales the local flux by a factor averaging 1.
+#[derive(Debug, Clone, Copy)]
+pub struct DiskTexture {
+    seed: u32,
+    contrast: f64, // 0 for a smooth disk
+    spiral_arms: u32,
+    pitch_angle: f64, // angle between the filaments and the circles r = const, in radians
+    spiral_weight: f64, // share of the filaments in the texture, the rest is turbulence
+}
+
+impl DiskTexture {
+    pub fn new(seed: u32) -> Self {
+        Self {
+            seed,
+            contrast: 0.8,
+            spiral_arms: 3,
+            pitch_angle: 0.25,
+            spiral_weight: 0.4,
+        }
+    }
+
+    pub fn seed(&self) -> u32 {
+        self.seed
+    }
+    pub fn contrast(&self) -> f64 {
+        self.contrast
+    }
+    pub fn spiral_arms(&self) -> u32 {
+        self.spiral_arms
+    }
+    pub fn pitch_angle(&self) -> f64 {
+        self.pitch_angle
+    }
+    pub fn spiral_weight(&self) -> f64 {
+        self.spiral_weight
+    }
+
+    pub fn with_contrast(self, contrast: f64) -> Self {
+        Self {
+            contrast: contrast.clamp(0., 1.),
+            ..self
+        }
+    }
+
+    // No arms leaves only the turbulence
+    pub fn with_spiral_arms(self, spiral_arms: u32, pitch_angle: f64) -> Self {
+        Self {
+            spiral_arms,
+            pitch_angle: pitch_angle.clamp(crate::DIV_EPSILON, PI / 2.),
+            ..self
+        }
+    }
+
+    fn turbulence(&self, log_radius: f64, azimuth: f64) -> f64 {
+        let mut cells = crate::DISK_TEXTURE_CELLS as i64;
+        let mut amplitude = 1.;
+        let (mut sum, mut total) = (0., 0.);
+        for octave in 0..crate::DISK_TEXTURE_OCTAVES {
+            let scale = cells as f64 / (2. * PI);
+            let noise = value_noise(
+                self.seed.wrapping_add(octave as u32),
+                log_radius * scale,
+                azimuth.rem_euclid(2. * PI) * scale,
+                cells,
+            );
+            sum += amplitude * noise;
+            total += amplitude;
+            cells *= 2;
+            amplitude *= 0.5;
+        }
+        sum / total
+    }
+
+    // Flux factor at the given radius and azimuth, both measured in the frame of the gas
+    pub fn sample(&self, radius: f64, azimuth: f64) -> f64 {
+        let log_radius = radius.max(crate::DIV_EPSILON).ln();
+        let turbulence = self.turbulence(log_radius, azimuth);
+
+        // Logarithmic spirals φ = ln r / tan(pitch) + const, wobbling with the turbulence
+        let pattern = if self.spiral_arms == 0 {
+            turbulence
+        } else {
+            let phase = self.spiral_arms as f64 * (azimuth - log_radius / self.pitch_angle.tan())
+                + 2. * PI * turbulence;
+            let spiral = 0.5 + 0.5 * phase.cos();
+            (1. - self.spiral_weight) * turbulence + self.spiral_weight * spiral
+        };
+
+        (1. + self.contrast * (2. * pattern - 1.)).max(0.)
+    }
+}
@@ -0,0 +1,109 @@
+use crate::SphericalState4D;
+use crate::geodesic::IntegrationError;
+use crate::{BlackHole, CartesianCoords3D, CartesianState3D, GeodesicState, Metric, Observer};
+
+// Camera falling freely along a timelike geodesic. Its 4-velocity is normalised to u.u = -1,
+// so the affine parameter of the integration is its proper time.
+#[derive(Debug, Clone, Copy)]
+pub struct FreeFallCamera<M: Metric<S>, S: GeodesicState = SphericalState4D> {
+    state: S,
+    metric: M,
+    black_hole: BlackHole,
+    dτ: f64, // next step suggested by the integrator
+    proper_time: f64,
+    initial_time: f64, // Boyer-Lindquist time of the initial state
+}
+
+impl<M: Metric<S>, S: GeodesicState + Copy> FreeFallCamera<M, S> {
+    // Starts at the position with the given velocity relative to the Static observer, in units
+    // of c, e.g. tangential and below the circular velocity for a plunging orbit
+    pub fn new(
+        black_hole: BlackHole,
+        metric: M,
+        position: CartesianCoords3D,
+        velocity: CartesianCoords3D,
+    ) -> Self {
+        let state = S::from_spatial(CartesianState3D::cartesian(
+            position.x(),
+            position.y(),
+            position.z(),
+            0.,
+            0.,
+            0.,
+        ));
+        let state = state.with_velocity(Observer::Boost(velocity).velocity(&metric, state));
+        Self {
+            state,
+            metric,
+            black_hole,
+            dτ: black_hole.radius() * crate::INTEGRATION_STEP_FACTOR,
+            proper_time: 0.,
+            initial_time: metric.coordinate_time(state),
+        }
+    }
+
+    // Radial plunge from rest at the position
+    pub fn from_rest(black_hole: BlackHole, metric: M, position: CartesianCoords3D) -> Self {
+        Self::new(
+            black_hole,
+            metric,
+            position,
+            CartesianCoords3D::cartesian(0., 0., 0.),
+        )
+    }
+
+    pub fn state(&self) -> S {
+        self.state
+    }
+    pub fn position(&self) -> CartesianCoords3D {
+        self.state.cartesian_position()
+    }
+    pub fn radius(&self) -> f64 {
+        self.metric.radius(self.state)
+    }
+    pub fn proper_time(&self) -> f64 {
+        self.proper_time
+    }
+    // Boyer-Lindquist time elapsed since the start of the fall
+    pub fn coordinate_time(&self) -> f64 {
+        self.metric.coordinate_time(self.state) - self.initial_time
+    }
+
+    // The camera sees the sky as an observer moving with its 4-velocity
+    pub fn observer(&self) -> Observer {
+        Observer::Velocity(self.state.velocity())
+    }
+
+    // Whether the camera reached the horizon, or the central singularity for metrics that go
+    // through the horizon, like Ray::step
+    pub fn has_fallen(&self) -> bool {
+        let r = self.metric.radius(self.state);
+        if self.metric.is_horizon_penetrating() {
+            r <= self.black_hole.radius() * crate::SINGULARITY_RADIUS_FACTOR
+        } else {
+            r <= self.black_hole.visual_radius()
+        }
+    }
+
+    // Integrates the trajectory over the given proper time, landing exactly on it. Coordinates
+    // that don't go through the horizon make the integration fail when the camera reaches it.
+    pub fn advance(&mut self, proper_time: f64) -> Result<(), IntegrationError> {
+        let target = self.proper_time + proper_time;
+        while target - self.proper_time > crate::DIV_EPSILON {
+            let dτ = self.dτ.min(target - self.proper_time);
+            let (state, taken, next) = crate::geodesic::solve_geodesic_rkf45_with_step(
+                self.state,
+                &self.metric,
+                self.black_hole.radius(),
+                dτ,
+            )?;
+            self.state = self.metric.renormalize_timelike(state);
+            self.proper_time += taken;
+            // A step shortened to land on the target says nothing about the step size
+            if dτ == self.dτ || taken < dτ {
+                self.dτ = next;
+            }
+        }
+        Ok(())
+    }
+}
@@ -1,47 +1,17 @@
 use std::ops::{Add, Mul, Sub};
 
-use crate::{SphericalState4D, tensors};
+use crate::{GeodesicState, Metric, Tensor2D, tensors};
 
 pub enum IntegrationError {
     MinStepReached,
     MaxRetriesReached,
 }
 
-fn geodesic(state: SphericalState4D, rs: f64) -> SphericalState4D {
-    let (sin_theta, cos_theta) = state.theta().sin_cos();
-    let altitude = (state.r() - rs).max(crate::DIV_EPSILON);
-
-    let d2t = -rs * state.dr() * state.dt() / (state.r() * altitude);
-
-    let d2r = {
-        let term1 = rs * altitude * state.dt().powi(2) / (2.0 * state.r().powi(3));
-        let term2 = rs / (2.0 * state.r() * altitude) * state.dr().powi(2);
-        let term3 = state.dtheta().powi(2) + (sin_theta * state.dphi()).powi(2);
-        -term1 + term2 + altitude * term3
-    };
-
-    let d2theta = {
-        let term1 = -2.0 * state.dr() * state.dtheta() / state.r();
-        let term2 = sin_theta * cos_theta * state.dphi().powi(2);
-        term1 + term2
-    };
-
-    let d2phi = {
-        let term1 = state.dr() / state.r();
-        let term2 = state.dtheta() * cos_theta / sin_theta;
-        -2.0 * state.dphi() * (term1 + term2)
-    };
-
-    SphericalState4D::spherical(
-        state.dt(),
-        state.dr(),
-        state.dtheta(),
-        state.dphi(),
-        d2t,
-        d2r,
-        d2theta,
-        d2phi,
-    )
+// Binet equation of a null orbit in Schwarzschild, with w = rs / r and ψ the angle
+// swept in the orbital plane: d²w/dψ² = 3/2 w² - w
+fn orbit_equation(state: Tensor2D) -> Tensor2D {
+    let (w, dw) = state.unpack();
+    Tensor2D::new(dw, 1.5 * w.powi(2) - w)
 }
 
 fn runge_kutta_fehlberg_45_pseudo_step<T, F>(state: T, h: f64, tol: f64, f: F) -> (T, f64, f64)
@@ -86,31 +56,70 @@ where
     (state_order_5, error, new_h)
 }
 
-fn runge_kutta_fehlberg_45<T, F>(
-    initial_state: T,
-    h: f64,
-    f: F,
+// Error tolerance, step bounds and number of retries of the adaptive step
+#[derive(Debug, Clone, Copy)]
+struct StepControl {
     tol: f64,
     min_h: f64,
     max_h: f64,
     max_retries: usize,
-) -> Result<(T, f64), IntegrationError>
+}
+
+impl StepControl {
+    // Geodesics around a black hole of Schwarzschild radius rs
+    fn geodesic(rs: f64) -> Self {
+        Self {
+            tol: rs * crate::RKF45_TOLERANCE_FACTOR,
+            min_h: rs * crate::RKF45_MIN_STEP_FACTOR,
+            max_h: rs * crate::RKF45_MAX_STEP_FACTOR,
+            max_retries: crate::RKF45_RETRIES,
+        }
+    }
+
+    // Orbit equation, integrated over the angle swept in the orbital plane
+    fn orbit() -> Self {
+        Self {
+            tol: crate::RKF45_TOLERANCE_FACTOR,
+            min_h: crate::ORBIT_MIN_STEP,
+            max_h: crate::ORBIT_MAX_STEP,
+            max_retries: crate::RKF45_RETRIES,
+        }
+    }
+}
+
+// on_try gets the step size and error estimate of every try, the last one being accepted
+// unless the step fails
+fn runge_kutta_fehlberg_45<T, F, G>(
+    initial_state: T,
+    h: f64,
+    f: F,
+    control: StepControl,
+    mut on_try: G,
+) -> Result<(T, f64, f64), IntegrationError>
 where
     F: Fn(T) -> T,
+    G: FnMut(f64, f64),
     T: Add<Output = T> + Sub<T, Output = T> + Mul<f64, Output = T> + tensors::Norm + Copy,
 {
+    let StepControl {
+        tol,
+        min_h,
+        max_h,
+        max_retries,
+    } = control;
     let mut current_h = h;
     let mut counter = 0;
 
     loop {
         let (new_state, error, mut new_h) =
             runge_kutta_fehlberg_45_pseudo_step(initial_state, current_h, tol, &f);
+        on_try(current_h, error);
 
         if error < tol {
             if new_h > current_h * 2.0 {
                 new_h = current_h * 2.0;
             }
-            return Ok((new_state, new_h.min(max_h)));
+            return Ok((new_state, current_h, new_h.min(max_h)));
         }
 
         counter += 1;
@@ -125,19 +134,49 @@ where
     }
 }
 
-pub fn solve_geodesic_rkf45(
-    initial_state: SphericalState4D,
+pub fn solve_geodesic_rkf45<S: GeodesicState, M: Metric<S>>(
+    initial_state: S,
+    metric: &M,
     rs: f64,
     h: f64,
-) -> Result<(SphericalState4D, f64), IntegrationError> {
-    let f = |state| geodesic(state, rs);
+) -> Result<(S, f64), IntegrationError> {
+    solve_geodesic_rkf45_with_step(initial_state, metric, rs, h)
+        .map(|(state, _, next_h)| (state, next_h))
+}
+
+// Also returns the step that was taken, which is smaller than h when the first try failed
+pub fn solve_geodesic_rkf45_with_step<S: GeodesicState, M: Metric<S>>(
+    initial_state: S,
+    metric: &M,
+    rs: f64,
+    h: f64,
+) -> Result<(S, f64, f64), IntegrationError> {
+    solve_geodesic_rkf45_with_tries(initial_state, metric, rs, h, |_, _| {})
+}
+
+// Same as solve_geodesic_rkf45_with_step, handing the step size and error estimate of every
+// try to on_try, rejected ones included
+pub fn solve_geodesic_rkf45_with_tries<S: GeodesicState, M: Metric<S>, G: FnMut(f64, f64)>(
+    initial_state: S,
+    metric: &M,
+    rs: f64,
+    h: f64,
+    on_try: G,
+) -> Result<(S, f64, f64), IntegrationError> {
+    let f = |state| metric.geodesic(state);
+    runge_kutta_fehlberg_45(initial_state, h, f, StepControl::geodesic(rs), on_try)
+}
+
+// Also returns the angle step that was taken, like solve_geodesic_rkf45_with_step
+pub fn solve_orbit_rkf45(
+    initial_state: Tensor2D,
+    h: f64,
+) -> Result<(Tensor2D, f64, f64), IntegrationError> {
     runge_kutta_fehlberg_45(
         initial_state,
         h,
-        f,
-        rs * crate::RKF45_TOLERANCE_FACTOR,
-        rs * crate::RKF45_MIN_STEP_FACTOR,
-        rs * crate::RKF45_MAX_STEP_FACTOR,
-        crate::RKF45_RETRIES,
+        orbit_equation,
+        StepControl::orbit(),
+        |_, _| {},
     )
 }
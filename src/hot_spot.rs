@@ -0,0 +1,63 @@
+use std::f64::consts::PI;
+
+// Compact Gaussian blob of hot gas on a circular equatorial orbit, moving with the disk at the
+// Keplerian angular velocity of its orbit. It adds to the flux of the disk, so it is only seen
+// where it overlaps the disk, between the ISCO and the outer edge.
+#[derive(Debug, Clone, Copy)]
+pub struct HotSpot {
+    orbit_radius: f64,
+    size: f64,      // standard deviation of the Gaussian, in the same unit as the radius
+    amplitude: f64, // flux at the center relative to the peak flux of the disk
+    phase: f64,     // azimuth at t = 0, in radians
+}
+
+impl HotSpot {
+    pub fn new(orbit_radius: f64, size: f64) -> Self {
+        Self {
+            orbit_radius,
+            size: size.max(crate::DIV_EPSILON),
+            amplitude: 10.,
+            phase: 0.,
+        }
+    }
+
+    pub fn orbit_radius(&self) -> f64 {
+        self.orbit_radius
+    }
+    pub fn size(&self) -> f64 {
+        self.size
+    }
+    pub fn amplitude(&self) -> f64 {
+        self.amplitude
+    }
+    pub fn phase(&self) -> f64 {
+        self.phase
+    }
+
+    pub fn with_amplitude(self, amplitude: f64) -> Self {
+        Self {
+            amplitude: amplitude.max(0.),
+            ..self
+        }
+    }
+
+    pub fn with_phase(self, phase: f64) -> Self {
+        Self {
+            phase: phase.rem_euclid(2. * PI),
+            ..self
+        }
+    }
+
+    // Azimuth of the center at the given coordinate time
+    pub fn azimuth(&self, angular_velocity: f64, time: f64) -> f64 {
+        self.phase + angular_velocity * time
+    }
+
+    // Flux of the spot relative to the peak flux of the disk at a point of the disk plane
+    pub fn flux(&self, radius: f64, azimuth: f64, angular_velocity: f64, time: f64) -> f64 {
+        let angle = azimuth - self.azimuth(angular_velocity, time);
+        let squared_distance = radius.powi(2) + self.orbit_radius.powi(2)
+            - 2. * radius * self.orbit_radius * angle.cos();
+        self.amplitude * (-0.5 * squared_distance / self.size.powi(2)).exp()
+    }
+}
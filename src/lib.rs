@@ -5,36 +5,65 @@ use std::{
 
 use macroquad::prelude::*;
 
+mod aov;
 mod backend;
 mod black_hole;
+mod commands;
 mod constants;
 mod cuda;
+mod debug_trace;
+mod deflection_table;
+mod disk_texture;
+mod free_fall;
 mod geodesic;
+mod hot_spot;
 mod hyperparameters;
+mod metric;
+mod observer;
+mod orbit;
+mod orbital_plane;
+mod polarisation;
 mod ray;
 mod scene;
+mod shadow;
 mod skybox;
+mod spectrum;
+mod star;
 mod tensors;
 mod threading;
 
+pub use aov::{AovBuffers, AovMode, HitKind, RayRecord};
 pub use backend::Backend;
-pub use black_hole::BlackHole;
+pub use black_hole::{BlackHole, EmissivityProfile};
+pub use commands::run_command;
 pub use constants::*;
 pub use cuda::*;
+pub use debug_trace::{RayTrace, StepTry, TraceStep};
+pub use deflection_table::DeflectionTable;
+pub use disk_texture::DiskTexture;
+pub use free_fall::FreeFallCamera;
+pub use geodesic::IntegrationError;
+pub use hot_spot::HotSpot;
 pub use hyperparameters::Hyperparameters;
+pub use metric::*;
+pub use observer::Observer;
+pub use orbit::{Orbit, OrbitEnd, OrbitPoint, OrbitSummary, Particle};
+pub use orbital_plane::PlanarRay;
+pub use polarisation::{MagneticField, Stokes};
 pub use ray::Ray;
 pub use scene::Scene;
 pub use skybox::*;
+pub use star::{Star, StarHit};
 pub use tensors::*;
 pub use threading::*;
 
 pub async fn launch() {
     clear_background(BLACK);
     next_frame().await;
-    let mut scene = Scene::new(
+    let mut scene: Scene<Kerr> = Scene::new(
         crate::SCENE_WIDTH_FACTOR,
         crate::SCENE_HEIGHT_FACTOR,
-        BlackHole::sagittarius(),
+        BlackHole::sagittarius().with_disk_texture(DiskTexture::new(0)),
     );
     let sleep = Duration::from_millis(1000);
 
@@ -52,26 +81,87 @@ pub async fn launch() {
         crate::RKF45_RETRIES,
     );
 
-    let mut backend = CUDABackend::new().await.unwrap_or_else(|e| panic!("{e}"));
+    // Without a GPU, the colour frames are traced on the CPU as well
+    let mut backend = match CUDABackend::new().await {
+        Ok(backend) => Some(backend),
+        Err(e) => {
+            println!("CUDA is unavailable ({e}), tracing on the CPU");
+            None
+        }
+    };
+
+    // The camera falls from rest, and starts over once it reaches the horizon. The time of the
+    // scene runs on from one fall to the next.
+    let fall_start = scene.camera().position();
+    let mut fall = FreeFallCamera::from_rest(scene.black_hole(), scene.metric(), fall_start);
+    let mut fall_time = scene.time(); // time of the scene when the fall started
+    let mut falling = true;
+    let mut show_critical_curve = false;
+    let mut aov_mode = AovMode::Color;
 
     clear_background(BLACK);
     next_frame().await;
 
     loop {
         let start = Instant::now();
-        let image = backend.compute(
-            &scene.black_hole().accretion_disk(),
-            &scene.black_hole(),
-            scene.skybox(),
-            &scene.camera(),
-            &scene,
-            &hyperparams,
-        );
-        if let Ok(im) = &image {
-            let texture = Texture2D::from_image(im);
+        // F stops the camera, which then stays at rest while the disk keeps turning, and drops
+        // it again from where it is
+        if is_key_pressed(KeyCode::F) {
+            falling = !falling;
+            if falling {
+                let position = scene.camera().position();
+                fall = FreeFallCamera::from_rest(scene.black_hole(), scene.metric(), position);
+                fall_time = scene.time();
+            } else {
+                scene.set_observer(Observer::Static);
+            }
+        }
+        // V cycles through the AOVs of the frame, traced on the CPU
+        if is_key_pressed(KeyCode::V) {
+            aov_mode = aov_mode.next();
+            println!("Showing {}", aov_mode.name());
+        }
+        let image = match (aov_mode, backend.as_mut()) {
+            (AovMode::Color, Some(backend)) => backend
+                .compute(
+                    &scene.black_hole().accretion_disk(),
+                    &scene.black_hole(),
+                    scene.skybox(),
+                    &scene.camera(),
+                    &scene,
+                    &hyperparams,
+                )
+                .unwrap_or_else(|e| {
+                    println!("CUDA frame failed ({e}), tracing it on the CPU");
+                    scene.get_image()
+                }),
+            (AovMode::Color, None) => scene.get_image(),
+            _ => {
+                let (image, aovs) = scene.get_image_with_aovs();
+                aovs.visualise(aov_mode).unwrap_or(image)
+            }
+        };
+        let texture = Texture2D::from_image(&image);
 
-            // Last color is the Hue, we want None
-            draw_texture(&texture, 0., 0., WHITE);
+        // Last color is the Hue, we want None
+        draw_texture(&texture, 0., 0., WHITE);
+
+        // C toggles the analytic edge of the shadow over the image
+        if is_key_pressed(KeyCode::C) {
+            show_critical_curve = !show_critical_curve;
+        }
+        if show_critical_curve {
+            let curve = scene.critical_curve();
+            for (start, end) in curve.iter().zip(curve.iter().cycle().skip(1)) {
+                draw_line(
+                    start.x() as f32,
+                    start.y() as f32,
+                    end.x() as f32,
+                    end.y() as f32,
+                    crate::CRITICAL_CURVE_THICKNESS,
+                    crate::CRITICAL_CURVE_COLOR,
+                );
+            }
         }
 
         next_frame().await;
@@ -81,6 +171,15 @@ pub async fn launch() {
             // thread::sleep(sleep - elapsed);
         }
         println!("{}", start.elapsed().as_millis());
-        scene.rotate_camera(1., 0.);
+        let frame_interval = scene.black_hole().radius() * crate::FRAME_TIME_FACTOR;
+        if falling {
+            if fall.advance(frame_interval).is_err() || fall.has_fallen() {
+                fall = FreeFallCamera::from_rest(scene.black_hole(), scene.metric(), fall_start);
+                fall_time = scene.time();
+            }
+            scene.follow(&fall, fall_time);
+        } else {
+            scene.advance_time(frame_interval);
+        }
     }
 }
@@ -11,8 +11,7 @@ fn window_conf() -> Conf {
     }
 }
 
-#[macroquad::main(window_conf)]
-async fn main() {
+fn main() {
     // TODO: use clap for CLI arguments
     // TODO: use log::{info, debug, warn};
 
@@ -29,5 +28,12 @@ async fn main() {
     //       // v_orbital ≈ sqrt(GM/r) pour Keplerian
     //   }
 
-    black_hole_sim::launch().await;
+    // Subcommands run without opening the window
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.is_empty() {
+        macroquad::Window::from_config(window_conf(), black_hole_sim::launch());
+    } else if let Err(error) = black_hole_sim::run_command(&args) {
+        eprintln!("{error}");
+        std::process::exit(1);
+    }
 }
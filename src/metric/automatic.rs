@@ -0,0 +1,217 @@
+use crate::{Dual, SphericalCoords4D, SphericalState4D};
+
+use super::{Metric, MetricComponents};
+
+pub type DualMetricComponents = [[Dual; 4]; 4];
+
+// Metric defined only by its components g_mn(x). The Christoffel symbols are never written
+// down: the derivatives of the metric come from evaluating it on dual numbers.
+#[derive(Debug, Clone, Copy)]
+pub struct AutoMetric<F> {
+    components: F,
+}
+
+impl<F> AutoMetric<F>
+where
+    F: Fn([Dual; 4]) -> DualMetricComponents,
+{
+    pub fn new(components: F) -> Self {
+        Self { components }
+    }
+
+    pub fn dual_components(&self, position: SphericalCoords4D) -> DualMetricComponents {
+        let (t, r, theta, phi) = position.unpack();
+        (self.components)(Dual::variables(t, r, theta, phi))
+    }
+}
+
+impl<F> Metric for AutoMetric<F>
+where
+    F: Fn([Dual; 4]) -> DualMetricComponents + Copy + Send + Sync + 'static,
+{
+    fn components(&self, position: SphericalCoords4D) -> MetricComponents {
+        values(&self.dual_components(position))
+    }
+
+    fn geodesic(&self, state: SphericalState4D) -> SphericalState4D {
+        let velocity = [state.dt(), state.dr(), state.dtheta(), state.dphi()];
+        let [d2t, d2r, d2theta, d2phi] =
+            geodesic_acceleration(&self.dual_components(state.position()), velocity);
+        SphericalState4D::spherical(
+            state.dt(),
+            state.dr(),
+            state.dtheta(),
+            state.dphi(),
+            d2t,
+            d2r,
+            d2theta,
+            d2phi,
+        )
+    }
+}
+
+pub(crate) fn values(g: &DualMetricComponents) -> MetricComponents {
+    g.map(|row| row.map(|component| component.value()))
+}
+
+fn partial(component: &Dual, index: usize) -> f64 {
+    let (a, b, c, d) = component.gradient().unpack();
+    [a, b, c, d][index]
+}
+
+// d2x^n = -g^nm (d_a g_mb - 1/2 d_m g_ab) u^a u^b, which is -Γ^n_ab u^a u^b
+pub(crate) fn geodesic_acceleration(g: &DualMetricComponents, u: [f64; 4]) -> [f64; 4] {
+    let mut force = [0.; 4];
+    for (m, force_m) in force.iter_mut().enumerate() {
+        for a in 0..4 {
+            for b in 0..4 {
+                let uu = u[a] * u[b];
+                *force_m += (partial(&g[m][b], a) - 0.5 * partial(&g[a][b], m)) * uu;
+            }
+        }
+    }
+
+    let inverse = invert(values(g));
+    let mut acceleration = [0.; 4];
+    for (n, acceleration_n) in acceleration.iter_mut().enumerate() {
+        for m in 0..4 {
+            *acceleration_n -= inverse[n][m] * force[m];
+        }
+    }
+    acceleration
+}
+
+// Gauss-Jordan elimination with partial pivoting
+pub(crate) fn invert(mut matrix: MetricComponents) -> MetricComponents {
+    let mut inverse = [[0.; 4]; 4];
+    for (i, row) in inverse.iter_mut().enumerate() {
+        row[i] = 1.;
+    }
+
+    for column in 0..4 {
+        let pivot = (column..4)
+            .max_by(|&i, &j| matrix[i][column].abs().total_cmp(&matrix[j][column].abs()))
+            .unwrap_or(column);
+        matrix.swap(column, pivot);
+        inverse.swap(column, pivot);
+
+        let scale = 1. / matrix[column][column];
+        for k in 0..4 {
+            matrix[column][k] *= scale;
+            inverse[column][k] *= scale;
+        }
+
+        for row in 0..4 {
+            if row == column {
+                continue;
+            }
+            let factor = matrix[row][column];
+            for k in 0..4 {
+                matrix[row][k] -= factor * matrix[column][k];
+                inverse[row][k] -= factor * inverse[column][k];
+            }
+        }
+    }
+    inverse
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Kerr, Schwarzschild};
+
+    const RS: f64 = 2.0;
+
+    fn schwarzschild_components([_, r, theta, _]: [Dual; 4]) -> DualMetricComponents {
+        let zero = Dual::constant(0.);
+        let f = 1. - RS / r;
+        let r2 = r.powi(2);
+        [
+            [-f, zero, zero, zero],
+            [zero, 1. / f, zero, zero],
+            [zero, zero, r2, zero],
+            [zero, zero, zero, r2 * theta.sin().powi(2)],
+        ]
+    }
+
+    #[test]
+    fn automatic_geodesic_matches_schwarzschild() {
+        let automatic = AutoMetric::new(schwarzschild_components);
+        let analytic = Schwarzschild::new(RS);
+
+        let states = [
+            SphericalState4D::spherical(0., 30., 1.2, 0.3, 0., -1., 0.01, 0.02),
+            SphericalState4D::spherical(5., 3.5, 0.4, -2.0, 0., 0.2, -0.05, 0.3),
+            SphericalState4D::spherical(-1., 7., 2.9, 4.0, 0., -0.6, 0.1, -0.08),
+        ];
+
+        for state in states {
+            let state = state.renormalize(&analytic);
+            let expected = analytic.geodesic(state).unpack();
+            let actual = automatic.geodesic(state).unpack();
+            let expected = [expected.4, expected.5, expected.6, expected.7];
+            let actual = [actual.4, actual.5, actual.6, actual.7];
+            for (e, a) in expected.iter().zip(actual) {
+                assert!(
+                    (e - a).abs() <= 1e-12 * e.abs().max(1.),
+                    "expected {expected:?}, got {actual:?}"
+                );
+            }
+        }
+    }
+
+    fn kerr_newman_components([_, r, theta, _]: [Dual; 4]) -> DualMetricComponents {
+        let zero = Dual::constant(0.);
+        let (a, q) = (0.6, 0.5);
+        let sin2 = theta.sin().powi(2);
+        let sigma = r.powi(2) + a * a * theta.cos().powi(2);
+        let potential = RS * r - q * q;
+        let delta = r.powi(2) - RS * r + a * a + q * q;
+        let tphi = -a * potential * sin2 / sigma;
+        [
+            [-(1. - potential / sigma), zero, zero, tphi],
+            [zero, sigma / delta, zero, zero],
+            [zero, zero, sigma, zero],
+            [
+                tphi,
+                zero,
+                zero,
+                (r.powi(2) + a * a + a * a * potential * sin2 / sigma) * sin2,
+            ],
+        ]
+    }
+
+    #[test]
+    fn automatic_geodesic_matches_kerr_newman() {
+        let automatic = AutoMetric::new(kerr_newman_components);
+        let analytic = Kerr::new(RS, 0.6).with_charge(0.5);
+
+        for state in [
+            SphericalState4D::spherical(0., 30., 1.2, 0.3, 0., -1., 0.01, 0.02),
+            SphericalState4D::spherical(5., 3.5, 0.4, -2.0, 0., 0.2, -0.05, 0.3),
+        ] {
+            let state = state.renormalize(&analytic);
+            let expected = analytic.geodesic(state).unpack();
+            let actual = automatic.geodesic(state).unpack();
+            let expected = [expected.4, expected.5, expected.6, expected.7];
+            let actual = [actual.4, actual.5, actual.6, actual.7];
+            for (e, a) in expected.iter().zip(actual) {
+                assert!(
+                    (e - a).abs() <= 1e-10 * e.abs().max(1.),
+                    "expected {expected:?}, got {actual:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn automatic_renormalization_matches_schwarzschild() {
+        let automatic = AutoMetric::new(schwarzschild_components);
+        let analytic = Schwarzschild::new(RS);
+        let state = SphericalState4D::spherical(0., 12., 1.0, 0.5, 0., -0.7, 0.03, -0.01);
+
+        let expected = analytic.renormalize(state).dt();
+        let actual = automatic.renormalize(state).dt();
+        assert!((expected - actual).abs() <= 1e-12 * expected.abs());
+    }
+}
@@ -0,0 +1,161 @@
+use crate::{BlackHole, CartesianState3D, Norm, SphericalCoords4D, SphericalState4D};
+
+use super::{Metric, MetricComponents};
+
+// Schwarzschild spacetime in ingoing Eddington-Finkelstein coordinates (v, r, theta, phi),
+// with v = t + r + rs ln|r / rs - 1| stored in the time slot of the state.
+// The metric is regular at r = rs, so cameras and rays can cross the horizon.
+#[derive(Debug, Clone, Copy)]
+pub struct EddingtonFinkelstein {
+    rs: f64,
+}
+
+impl EddingtonFinkelstein {
+    pub fn new(rs: f64) -> Self {
+        Self { rs }
+    }
+
+    pub fn rs(&self) -> f64 {
+        self.rs
+    }
+
+    // Returns f(r) = 1 - rs / r and f'(r)
+    fn f(&self, r: f64) -> (f64, f64) {
+        (1.0 - self.rs / r, self.rs / r.powi(2))
+    }
+}
+
+impl From<BlackHole> for EddingtonFinkelstein {
+    fn from(value: BlackHole) -> Self {
+        Self::new(value.radius())
+    }
+}
+
+impl Metric for EddingtonFinkelstein {
+    fn components(&self, position: SphericalCoords4D) -> MetricComponents {
+        let r = position.r();
+        let (f, _) = self.f(r);
+        let r2 = r.powi(2);
+        [
+            [-f, 1., 0., 0.],
+            [1., 0., 0., 0.],
+            [0., 0., r2, 0.],
+            [0., 0., 0., r2 * position.theta().sin().powi(2)],
+        ]
+    }
+
+    fn geodesic(&self, state: SphericalState4D) -> SphericalState4D {
+        let r = state.r();
+        let (sin_theta, cos_theta) = state.theta().sin_cos();
+        let (f, df) = self.f(r);
+        let (dv, dr) = (state.dt(), state.dr());
+        let angular = state.dtheta().powi(2) + (sin_theta * state.dphi()).powi(2);
+
+        let d2v = -0.5 * df * dv.powi(2) + r * angular;
+        let d2r = -0.5 * f * df * dv.powi(2) + df * dv * dr + r * f * angular;
+
+        let d2theta = {
+            let term1 = -2.0 * dr * state.dtheta() / r;
+            let term2 = sin_theta * cos_theta * state.dphi().powi(2);
+            term1 + term2
+        };
+
+        let d2phi = {
+            let term1 = dr / r;
+            let term2 = state.dtheta() * cos_theta / sin_theta;
+            -2.0 * state.dphi() * (term1 + term2)
+        };
+
+        SphericalState4D::spherical(
+            dv,
+            dr,
+            state.dtheta(),
+            state.dphi(),
+            d2v,
+            d2r,
+            d2theta,
+            d2phi,
+        )
+    }
+
+    fn renormalize(&self, state: SphericalState4D) -> SphericalState4D {
+        // Null condition: f dv² - 2 dr dv - r² dΩ² = 0. Both roots are valid null vectors,
+        // keep the one closest to the current dv so the ray stays on its branch.
+        let r = state.r();
+        let (f, _) = self.f(r);
+        let dr = state.dr();
+        let angular =
+            r.powi(2) * (state.dtheta().powi(2) + (state.theta().sin() * state.dphi()).powi(2));
+
+        let discriminant = (dr.powi(2) + f * angular).max(0.).sqrt();
+        let q = dr + dr.signum() * discriminant;
+        let finite_root = -angular / q;
+        let dv = if f.abs() > crate::DIV_EPSILON {
+            let other_root = q / f;
+            if (other_root - state.dt()).abs() < (finite_root - state.dt()).abs() {
+                other_root
+            } else {
+                finite_root
+            }
+        } else {
+            finite_root
+        };
+
+        SphericalState4D::spherical(
+            state.t(),
+            state.r(),
+            state.theta(),
+            state.phi(),
+            dv,
+            state.dr(),
+            state.dtheta(),
+            state.dphi(),
+        )
+    }
+
+    fn observer_velocity(&self, position: SphericalCoords4D) -> [f64; 4] {
+        // Free fall from rest at infinity, see null_state
+        let beta = (self.rs / position.r()).sqrt();
+        [1. / (1. + beta), -beta, 0., 0.]
+    }
+
+    fn null_state(&self, spatial_state: CartesianState3D) -> SphericalState4D {
+        // The camera free-falls from rest at infinity. Its 4-velocity and radial unit vector are
+        // u = (1 / (1 + β), -β) and e_r = (1 / (1 + β), 1) in (v, r), with β = sqrt(rs / r),
+        // and both stay regular across the horizon.
+        // Rays are traced back in time, along -u + n with n the viewing direction.
+        let spherical = spatial_state.to_spherical();
+        let r = spherical.r();
+        let sin_theta = spherical.theta().sin();
+        let beta = (self.rs / r).sqrt();
+
+        let n = crate::SphericalCoords3D::spherical(
+            spherical.dr(),
+            r * spherical.dtheta(),
+            r * sin_theta * spherical.dphi(),
+        )
+        .normalize();
+
+        SphericalState4D::spherical(
+            0.,
+            r,
+            spherical.theta(),
+            spherical.phi(),
+            (n.r() - 1.) / (1. + beta),
+            beta + n.r(),
+            n.theta() / r,
+            n.phi() / (r * sin_theta),
+        )
+    }
+
+    // t = v - r - rs ln|r / rs - 1|, which diverges at the horizon and is clamped there
+    fn coordinate_time(&self, state: SphericalState4D) -> f64 {
+        let r = state.r();
+        let tortoise = r + self.rs * (r / self.rs - 1.).abs().max(crate::DIV_EPSILON).ln();
+        state.t() - tortoise
+    }
+
+    fn is_horizon_penetrating(&self) -> bool {
+        true
+    }
+}
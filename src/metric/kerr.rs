@@ -0,0 +1,209 @@
+use crate::{BlackHole, SphericalCoords4D, SphericalState4D};
+
+use super::{
+    Metric, MetricComponents, boyer_lindquist_carter_constant, boyer_lindquist_walker_penrose,
+};
+
+// Non-zero Boyer-Lindquist components of the Kerr-Newman metric (G = c = 1, rs = 2M), where
+// the charge turns rs r into rs r - Q² and adds Q² to Δ.
+#[derive(Debug, Clone, Copy)]
+struct KerrComponents {
+    tt: f64,
+    tphi: f64,
+    rr: f64,
+    thetatheta: f64,
+    phiphi: f64,
+}
+
+impl KerrComponents {
+    // Returns the metric along with its derivatives with respect to r and theta
+    fn with_derivatives(r: f64, theta: f64, rs: f64, a: f64, q: f64) -> (Self, Self, Self) {
+        let (sin_theta, cos_theta) = theta.sin_cos();
+        let sin2 = sin_theta.powi(2);
+        let a2 = a.powi(2);
+        let r2 = r.powi(2);
+
+        let sigma = r2 + a2 * cos_theta.powi(2);
+        let sigma2 = sigma.powi(2);
+        let dsigma_dtheta = -2.0 * a2 * sin_theta * cos_theta;
+        let delta = (r2 - rs * r + a2 + q.powi(2)).max(crate::DIV_EPSILON);
+        let ddelta_dr = 2.0 * r - rs;
+
+        // rs r - Q², and the derivative of its ratio to Σ with respect to r
+        let potential = rs * r - q.powi(2);
+        let dpotential_dr = (rs * sigma - 2.0 * r * potential) / sigma2;
+
+        let metric = Self {
+            tt: -(1.0 - potential / sigma),
+            tphi: -potential * a * sin2 / sigma,
+            rr: sigma / delta,
+            thetatheta: sigma,
+            phiphi: (r2 + a2) * sin2 + potential * a2 * sin2.powi(2) / sigma,
+        };
+
+        let d_dr = Self {
+            tt: dpotential_dr,
+            tphi: -a * sin2 * dpotential_dr,
+            rr: (2.0 * r * delta - sigma * ddelta_dr) / delta.powi(2),
+            thetatheta: 2.0 * r,
+            phiphi: 2.0 * r * sin2 + a2 * sin2.powi(2) * dpotential_dr,
+        };
+
+        let d_dtheta = Self {
+            tt: -potential * dsigma_dtheta / sigma2,
+            tphi: -potential * a * (2.0 * sin_theta * cos_theta * sigma - sin2 * dsigma_dtheta)
+                / sigma2,
+            rr: dsigma_dtheta / delta,
+            thetatheta: dsigma_dtheta,
+            phiphi: 2.0 * (r2 + a2) * sin_theta * cos_theta
+                + potential
+                    * a2
+                    * (4.0 * sin2 * sin_theta * cos_theta * sigma - sin2.powi(2) * dsigma_dtheta)
+                    / sigma2,
+        };
+
+        (metric, d_dr, d_dtheta)
+    }
+
+    // g_ab u^a u^b, with u = (dt, dr, dtheta, dphi)
+    fn contract(&self, state: &SphericalState4D) -> f64 {
+        self.tt * state.dt().powi(2)
+            + 2.0 * self.tphi * state.dt() * state.dphi()
+            + self.rr * state.dr().powi(2)
+            + self.thetatheta * state.dtheta().powi(2)
+            + self.phiphi * state.dphi().powi(2)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Kerr {
+    rs: f64,
+    a: f64,
+    q: f64,
+}
+
+impl Kerr {
+    pub fn new(rs: f64, a: f64) -> Self {
+        Self { rs, a, q: 0. }
+    }
+
+    // Kerr-Newman black hole of charge Q, in the same units as ReissnerNordstrom
+    pub fn with_charge(self, q: f64) -> Self {
+        Self { q, ..self }
+    }
+
+    pub fn rs(&self) -> f64 {
+        self.rs
+    }
+
+    pub fn a(&self) -> f64 {
+        self.a
+    }
+
+    pub fn q(&self) -> f64 {
+        self.q
+    }
+}
+
+impl From<BlackHole> for Kerr {
+    fn from(value: BlackHole) -> Self {
+        Self::new(value.radius(), value.spin()).with_charge(value.charge())
+    }
+}
+
+impl Metric for Kerr {
+    fn components(&self, position: SphericalCoords4D) -> MetricComponents {
+        let (g, _, _) = KerrComponents::with_derivatives(
+            position.r(),
+            position.theta(),
+            self.rs,
+            self.a,
+            self.q,
+        );
+        [
+            [g.tt, 0., 0., g.tphi],
+            [0., g.rr, 0., 0.],
+            [0., 0., g.thetatheta, 0.],
+            [g.tphi, 0., 0., g.phiphi],
+        ]
+    }
+
+    fn geodesic(&self, state: SphericalState4D) -> SphericalState4D {
+        let (g, dg_dr, dg_dtheta) =
+            KerrComponents::with_derivatives(state.r(), state.theta(), self.rs, self.a, self.q);
+        let (dt, dr, dtheta, dphi) = (state.dt(), state.dr(), state.dtheta(), state.dphi());
+
+        // Geodesic equation in covariant form: g_mn d2x^n = -(d_a g_mb - 1/2 d_m g_ab) u^a u^b
+        // The metric only depends on r and theta, so only those derivatives survive.
+        let force_t = dr * (dg_dr.tt * dt + dg_dr.tphi * dphi)
+            + dtheta * (dg_dtheta.tt * dt + dg_dtheta.tphi * dphi);
+        let force_phi = dr * (dg_dr.tphi * dt + dg_dr.phiphi * dphi)
+            + dtheta * (dg_dtheta.tphi * dt + dg_dtheta.phiphi * dphi);
+        let force_r =
+            dr * dr * dg_dr.rr + dtheta * dr * dg_dtheta.rr - 0.5 * dg_dr.contract(&state);
+        let force_theta = dr * dtheta * dg_dr.thetatheta + dtheta * dtheta * dg_dtheta.thetatheta
+            - 0.5 * dg_dtheta.contract(&state);
+
+        // Invert the (t, phi) block, the (r, theta) part is diagonal
+        let det = (g.tt * g.phiphi - g.tphi.powi(2)).min(-crate::DIV_EPSILON);
+        let d2t = -(g.phiphi * force_t - g.tphi * force_phi) / det;
+        let d2phi = -(g.tt * force_phi - g.tphi * force_t) / det;
+        let d2r = -force_r / g.rr;
+        let d2theta = -force_theta / g.thetatheta;
+
+        SphericalState4D::spherical(dt, dr, dtheta, dphi, d2t, d2r, d2theta, d2phi)
+    }
+
+    fn walker_penrose(
+        &self,
+        position: SphericalCoords4D,
+        k: [f64; 4],
+        f: [f64; 4],
+    ) -> Option<(f64, f64)> {
+        Some(boyer_lindquist_walker_penrose(self.a, position, k, f))
+    }
+
+    fn carter_constant(&self, position: SphericalCoords4D, u: [f64; 4]) -> Option<f64> {
+        let g = self.components(position);
+        Some(boyer_lindquist_carter_constant(self.a, position, &g, u))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ray::StoppingCriterion;
+    use crate::{CartesianCoords4D, CartesianState3D, Ray};
+
+    // Light passing along the rotation of the hole gets closer to it without being captured,
+    // which flattens that side of the shadow. For a = 0.9M the critical impact parameters are
+    // about 2.9M along the rotation and 6.9M against it.
+    #[test]
+    fn shadow_is_flattened_on_the_prograde_side() {
+        let black_hole = BlackHole::kerr(CartesianCoords4D::cartesian(0., 0., 0., 0.), 1., 0.9);
+        let metric = Kerr::from(black_hole);
+        let (distance, impact_parameter) = (50., 4.5);
+        let bounding_box_radius = black_hole.radius() * crate::BOUNDING_BOX_FACTOR;
+
+        // The camera is on the x axis looking at the hole, which spins towards +y there
+        let is_captured = |side: f64| {
+            let sin = side * impact_parameter / distance;
+            let direction =
+                CartesianState3D::cartesian(distance, 0., 0., -(1. - sin.powi(2)).sqrt(), sin, 0.);
+            let mut ray = Ray::new(direction, metric, black_hole.radius() * 0.1);
+            for _ in 0..crate::NUM_INTEGRATION_STEPS {
+                match ray.step(black_hole, bounding_box_radius) {
+                    Some(StoppingCriterion::EnteredEventHorizon) => return true,
+                    Some(StoppingCriterion::OutOfBoundingBox(_)) => return false,
+                    // The ray stays in the equatorial plane, through the disk
+                    _ => {}
+                }
+            }
+            panic!("the ray neither escaped nor fell in");
+        };
+
+        // Light reaching the camera from +y moves towards -x there, against the rotation
+        assert!(is_captured(1.), "retrograde light should be captured");
+        assert!(!is_captured(-1.), "prograde light should escape");
+    }
+}
@@ -0,0 +1,138 @@
+use crate::{BlackHole, CartesianCoords3D, CartesianCoords4D, CartesianState4D, Dual, Scalar};
+
+use super::automatic::{DualMetricComponents, geodesic_acceleration, values};
+use super::{Metric, MetricComponents};
+
+// Kerr-Newman spacetime in Cartesian Kerr-Schild coordinates (t, x, y, z): g = η + f l ⊗ l.
+// There is no polar axis in these coordinates, so rays crossing theta = 0 or pi
+// are integrated like any other.
+#[derive(Debug, Clone, Copy)]
+pub struct KerrSchild {
+    rs: f64,
+    a: f64,
+    q: f64,
+}
+
+impl KerrSchild {
+    pub fn new(rs: f64, a: f64) -> Self {
+        Self { rs, a, q: 0. }
+    }
+
+    // Kerr-Newman black hole of charge Q, see Kerr::with_charge
+    pub fn with_charge(self, q: f64) -> Self {
+        Self { q, ..self }
+    }
+
+    pub fn rs(&self) -> f64 {
+        self.rs
+    }
+
+    pub fn a(&self) -> f64 {
+        self.a
+    }
+
+    pub fn q(&self) -> f64 {
+        self.q
+    }
+
+    fn dual_components(&self, position: CartesianCoords4D) -> DualMetricComponents {
+        let (t, x, y, z) = position.unpack();
+        let [_, x, y, z] = Dual::variables(t, x, y, z);
+        let a2 = self.a.powi(2);
+
+        let r = boyer_lindquist_radius(self.a, x, y, z);
+        let r2 = r.powi(2);
+
+        let f = (self.rs * r - self.q.powi(2)) * r2 / (r2.powi(2) + a2 * z.powi(2));
+        let l = [
+            Dual::constant(1.),
+            (r * x + y * self.a) / (r2 + a2),
+            (r * y - x * self.a) / (r2 + a2),
+            z / r,
+        ];
+
+        let mut g = [[Dual::constant(0.); 4]; 4];
+        for (i, row) in g.iter_mut().enumerate() {
+            for (j, component) in row.iter_mut().enumerate() {
+                let minkowski = match (i, j) {
+                    (0, 0) => -1.,
+                    _ if i == j => 1.,
+                    _ => 0.,
+                };
+                *component = f * l[i] * l[j] + minkowski;
+            }
+        }
+        g
+    }
+}
+
+impl From<BlackHole> for KerrSchild {
+    fn from(value: BlackHole) -> Self {
+        Self::new(value.radius(), value.spin()).with_charge(value.charge())
+    }
+}
+
+// Boyer-Lindquist radius, from x² + y² + z² = r² + a² (1 - z² / r²)
+fn boyer_lindquist_radius<T: Scalar>(a: f64, x: T, y: T, z: T) -> T {
+    let w = x.powi(2) + y.powi(2) + z.powi(2) - a.powi(2);
+    (((w.powi(2) + z.powi(2) * (4. * a.powi(2))).sqrt() + w) * 0.5).sqrt()
+}
+
+impl Metric<CartesianState4D> for KerrSchild {
+    fn components(&self, position: CartesianCoords4D) -> MetricComponents {
+        values(&self.dual_components(position))
+    }
+
+    fn radius(&self, state: CartesianState4D) -> f64 {
+        boyer_lindquist_radius(self.a, state.x(), state.y(), state.z())
+    }
+
+    // x + iy = (r + ia) sin θ e^(iφ) and z = r cos θ, φ being the azimuth of the ingoing
+    // Kerr coordinates
+    fn cartesian_position(&self, state: CartesianState4D) -> CartesianCoords3D {
+        let r = self.radius(state);
+        let azimuth = state.y().atan2(state.x()) - self.a.atan2(r);
+        let cylindrical = state.x().hypot(state.y()) * r / r.hypot(self.a);
+        CartesianCoords3D::cartesian(
+            cylindrical * azimuth.cos(),
+            cylindrical * azimuth.sin(),
+            state.z(),
+        )
+    }
+
+    // dt = dt_KS - (rs r - Q²) / Δ dr along the ingoing principal null directions, integrated
+    // between the horizons r± = M ± sqrt(M² - a² - Q²), up to a constant
+    fn coordinate_time(&self, state: CartesianState4D) -> f64 {
+        let r = self.radius(state);
+        let mass = 0.5 * self.rs;
+        let q2 = self.q.powi(2);
+        let root = (mass.powi(2) - self.a.powi(2) - q2).max(0.).sqrt();
+        let (r_plus, r_minus) = (mass + root, mass - root);
+        let ln = |x: f64| x.abs().max(crate::DIV_EPSILON).ln();
+        let shift = if root > crate::DIV_EPSILON {
+            ((self.rs * r_plus - q2) * ln(r - r_plus) - (self.rs * r_minus - q2) * ln(r - r_minus))
+                / (r_plus - r_minus)
+        } else {
+            // Extremal hole, Δ = (r - M)²
+            self.rs * ln(r - mass)
+                - (self.rs * mass - q2) / (r - mass).abs().max(crate::DIV_EPSILON)
+        };
+        state.t() - shift
+    }
+
+    fn geodesic(&self, state: CartesianState4D) -> CartesianState4D {
+        let velocity = [state.dt(), state.dx(), state.dy(), state.dz()];
+        let [d2t, d2x, d2y, d2z] =
+            geodesic_acceleration(&self.dual_components(state.position()), velocity);
+        CartesianState4D::cartesian(
+            state.dt(),
+            state.dx(),
+            state.dy(),
+            state.dz(),
+            d2t,
+            d2x,
+            d2y,
+            d2z,
+        )
+    }
+}
@@ -0,0 +1,167 @@
+mod automatic;
+mod eddington_finkelstein;
+mod kerr;
+mod kerr_schild;
+mod reissner_nordstrom;
+mod schwarzschild;
+
+pub use automatic::{AutoMetric, DualMetricComponents};
+pub use eddington_finkelstein::EddingtonFinkelstein;
+pub use kerr::Kerr;
+pub use kerr_schild::KerrSchild;
+pub use reissner_nordstrom::ReissnerNordstrom;
+pub use schwarzschild::Schwarzschild;
+
+use crate::{
+    CartesianCoords3D, CartesianState3D, GeodesicState, SphericalCoords4D, SphericalState4D,
+};
+
+// Covariant components g_mn, indices ordered as the state coordinates, time first
+pub type MetricComponents = [[f64; 4]; 4];
+
+pub trait Metric<S: GeodesicState = SphericalState4D>: Copy + Send + Sync + 'static {
+    fn components(&self, position: S::Position) -> MetricComponents;
+
+    // Returns d/dλ of the state: the velocity followed by the geodesic acceleration
+    fn geodesic(&self, state: S) -> S;
+
+    // Solves g_mn u^m u^n = 0 for dt, keeping the spatial part of the 4-velocity.
+    // Rays are traced backwards in time from the camera, so the velocity is minus the photon momentum.
+    fn renormalize(&self, state: S) -> S {
+        let g = self.components(state.coordinates());
+        let velocity = state.velocity();
+
+        let mut cross = 0.;
+        let mut spatial = 0.;
+        for i in 1..4 {
+            cross += g[0][i] * velocity[i];
+            for j in 1..4 {
+                spatial += g[i][j] * velocity[i] * velocity[j];
+            }
+        }
+        let discriminant = (cross.powi(2) - g[0][0] * spatial).max(0.).sqrt();
+
+        // Past-directed root, written so that it stays finite when g_tt vanishes (ergosphere)
+        let dt = -spatial / (discriminant + cross).max(crate::DIV_EPSILON);
+        state.with_dt(dt)
+    }
+
+    // Solves g_mn u^m u^n = -1 for dt, keeping the spatial part of the 4-velocity, for massive
+    // particles moving forward in time
+    fn renormalize_timelike(&self, state: S) -> S {
+        let g = self.components(state.coordinates());
+        let velocity = state.velocity();
+
+        let mut cross = 0.;
+        let mut spatial = 0.;
+        for i in 1..4 {
+            cross += g[0][i] * velocity[i];
+            for j in 1..4 {
+                spatial += g[i][j] * velocity[i] * velocity[j];
+            }
+        }
+        let discriminant = (cross.powi(2) - g[0][0] * (spatial + 1.)).max(0.).sqrt();
+
+        // Future-directed root, finite when g_tt vanishes like in renormalize
+        let dt = (spatial + 1.) / (discriminant - cross).max(crate::DIV_EPSILON);
+        state.with_dt(dt)
+    }
+
+    // Lowers the index of the state velocity, k_m = g_mn k^n
+    fn covariant_velocity(&self, state: S) -> [f64; 4] {
+        let g = self.components(state.coordinates());
+        let velocity = state.velocity();
+        g.map(|row| row.iter().zip(velocity).map(|(g, v)| g * v).sum())
+    }
+
+    // 4-velocity of the camera, the zero angular momentum observer by default, which is at rest
+    // outside of a spinning black hole and also exists inside its ergosphere
+    fn observer_velocity(&self, position: S::Position) -> [f64; 4] {
+        crate::observer::zamo_velocity(&self.components(position))
+    }
+
+    // Initial null 4-velocity of a ray leaving the camera along the given direction
+    fn null_state(&self, spatial_state: CartesianState3D) -> S {
+        self.renormalize(S::from_spatial(spatial_state))
+    }
+
+    // Boyer-Lindquist radius of the state, which the horizon, the disk and the bounding box are
+    // measured with
+    fn radius(&self, state: S) -> f64 {
+        state.radius()
+    }
+
+    // Position of the state in the Cartesian frame of the Boyer-Lindquist coordinates,
+    // r (sin θ cos φ, sin θ sin φ, cos θ), where disk crossings and star hits are checked
+    fn cartesian_position(&self, state: S) -> CartesianCoords3D {
+        state.cartesian_position()
+    }
+
+    // Boyer-Lindquist time of the state, up to a constant, which delays and the scene time are
+    // measured in
+    fn coordinate_time(&self, state: S) -> f64 {
+        state.time()
+    }
+
+    // Whether rays can be integrated through the event horizon
+    fn is_horizon_penetrating(&self) -> bool {
+        false
+    }
+
+    // Walker-Penrose constant κ1 + iκ2 of a polarisation vector f orthogonal to the ray k, which
+    // stays constant while f is parallel-transported along it. None when the metric has no such
+    // constant in its coordinates, and light is then left unpolarised.
+    fn walker_penrose(
+        &self,
+        _position: S::Position,
+        _k: [f64; 4],
+        _f: [f64; 4],
+    ) -> Option<(f64, f64)> {
+        None
+    }
+
+    // Carter constant Q of a geodesic with velocity u, conserved along it with the energy and
+    // the angular momentum around the z axis. None when the metric has none in its coordinates.
+    fn carter_constant(&self, _position: S::Position, _u: [f64; 4]) -> Option<f64> {
+        None
+    }
+}
+
+// Walker & Penrose (1970) in Boyer-Lindquist coordinates, for the spin parameter a:
+// κ = (A - iB)(r - ia cos θ) with
+// A = (k^t f^r - k^r f^t) + a sin²θ (k^r f^φ - k^φ f^r)
+// B = [(r² + a²)(k^φ f^θ - k^θ f^φ) - a (k^t f^θ - k^θ f^t)] sin θ
+// The charge of Kerr-Newman doesn't enter it.
+pub(crate) fn boyer_lindquist_walker_penrose(
+    a: f64,
+    position: SphericalCoords4D,
+    k: [f64; 4],
+    f: [f64; 4],
+) -> (f64, f64) {
+    let r = position.r();
+    let (sin_theta, cos_theta) = position.theta().sin_cos();
+    let a_term = (k[0] * f[1] - k[1] * f[0]) + a * sin_theta.powi(2) * (k[1] * f[3] - k[3] * f[1]);
+    let b_term = ((r.powi(2) + a.powi(2)) * (k[3] * f[2] - k[2] * f[3])
+        - a * (k[0] * f[2] - k[2] * f[0]))
+        * sin_theta;
+    (
+        a_term * r - b_term * a * cos_theta,
+        -(a_term * a * cos_theta + b_term * r),
+    )
+}
+
+// Carter (1968) in Boyer-Lindquist coordinates, for the spin parameter a:
+// Q = p_θ² + cos²θ [a² (μ² - E²) + L_z² / sin²θ] with μ² = -u.u, E = -p_t and L_z = p_φ.
+// It is the same with the charge of Kerr-Newman, for an uncharged particle.
+pub(crate) fn boyer_lindquist_carter_constant(
+    a: f64,
+    position: SphericalCoords4D,
+    g: &MetricComponents,
+    u: [f64; 4],
+) -> f64 {
+    let p: [f64; 4] = g.map(|row| row.iter().zip(u).map(|(g, u)| g * u).sum());
+    let mass2 = -p.iter().zip(u).map(|(p, u)| p * u).sum::<f64>();
+    let (sin_theta, cos_theta) = position.theta().sin_cos();
+    let sin2 = sin_theta.powi(2).max(crate::DIV_EPSILON);
+    p[2].powi(2) + cos_theta.powi(2) * (a.powi(2) * (mass2 - p[0].powi(2)) + p[3].powi(2) / sin2)
+}
@@ -0,0 +1,131 @@
+use crate::{BlackHole, SphericalCoords4D, SphericalState4D};
+
+use super::{
+    Metric, MetricComponents, boyer_lindquist_carter_constant, boyer_lindquist_walker_penrose,
+};
+
+// Charged, non-rotating black hole. In geometrised Gaussian units the metric
+// function is f(r) = 1 - rs / r + Q² / r².
+#[derive(Debug, Clone, Copy)]
+pub struct ReissnerNordstrom {
+    rs: f64,
+    q: f64,
+}
+
+impl ReissnerNordstrom {
+    pub fn new(rs: f64, q: f64) -> Self {
+        Self { rs, q }
+    }
+
+    pub fn rs(&self) -> f64 {
+        self.rs
+    }
+
+    pub fn q(&self) -> f64 {
+        self.q
+    }
+
+    // Returns f(r) and f'(r)
+    fn f(&self, r: f64) -> (f64, f64) {
+        let q2 = self.q.powi(2);
+        let f = 1.0 - self.rs / r + q2 / r.powi(2);
+        let df = self.rs / r.powi(2) - 2.0 * q2 / r.powi(3);
+        (f, df)
+    }
+}
+
+impl From<BlackHole> for ReissnerNordstrom {
+    fn from(value: BlackHole) -> Self {
+        Self::new(value.radius(), value.charge())
+    }
+}
+
+impl Metric for ReissnerNordstrom {
+    fn components(&self, position: SphericalCoords4D) -> MetricComponents {
+        let r = position.r();
+        let (f, _) = self.f(r);
+        let r2 = r.powi(2);
+        [
+            [-f, 0., 0., 0.],
+            [0., 1.0 / f, 0., 0.],
+            [0., 0., r2, 0.],
+            [0., 0., 0., r2 * position.theta().sin().powi(2)],
+        ]
+    }
+
+    fn geodesic(&self, state: SphericalState4D) -> SphericalState4D {
+        let (sin_theta, cos_theta) = state.theta().sin_cos();
+        let (f, df) = self.f(state.r());
+        let f = f.max(crate::DIV_EPSILON);
+
+        let d2t = -df / f * state.dr() * state.dt();
+
+        let d2r = {
+            let term1 = 0.5 * f * df * state.dt().powi(2);
+            let term2 = 0.5 * df / f * state.dr().powi(2);
+            let term3 = state.dtheta().powi(2) + (sin_theta * state.dphi()).powi(2);
+            -term1 + term2 + state.r() * f * term3
+        };
+
+        let d2theta = {
+            let term1 = -2.0 * state.dr() * state.dtheta() / state.r();
+            let term2 = sin_theta * cos_theta * state.dphi().powi(2);
+            term1 + term2
+        };
+
+        let d2phi = {
+            let term1 = state.dr() / state.r();
+            let term2 = state.dtheta() * cos_theta / sin_theta;
+            -2.0 * state.dphi() * (term1 + term2)
+        };
+
+        SphericalState4D::spherical(
+            state.dt(),
+            state.dr(),
+            state.dtheta(),
+            state.dphi(),
+            d2t,
+            d2r,
+            d2theta,
+            d2phi,
+        )
+    }
+
+    fn renormalize(&self, state: SphericalState4D) -> SphericalState4D {
+        let r = state.r();
+        let r2 = r.powi(2);
+        let (f, _) = self.f(r);
+        let f = f.max(crate::DIV_EPSILON);
+
+        let spatial = state.dr().powi(2) / f
+            + r2 * state.dtheta().powi(2)
+            + r2 * (state.theta().sin() * state.dphi()).powi(2);
+
+        // Past-directed, rays are traced backwards in time from the camera
+        let dt = -f64::sqrt(spatial / f);
+        SphericalState4D::spherical(
+            state.t(),
+            state.r(),
+            state.theta(),
+            state.phi(),
+            dt,
+            state.dr(),
+            state.dtheta(),
+            state.dphi(),
+        )
+    }
+
+    fn walker_penrose(
+        &self,
+        position: SphericalCoords4D,
+        k: [f64; 4],
+        f: [f64; 4],
+    ) -> Option<(f64, f64)> {
+        Some(boyer_lindquist_walker_penrose(0., position, k, f))
+    }
+
+    fn carter_constant(&self, position: SphericalCoords4D, u: [f64; 4]) -> Option<f64> {
+        let g = self.components(position);
+        Some(boyer_lindquist_carter_constant(0., position, &g, u))
+    }
+}
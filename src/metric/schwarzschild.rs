@@ -0,0 +1,117 @@
+use crate::{BlackHole, SphericalCoords4D, SphericalState4D};
+
+use super::{
+    Metric, MetricComponents, boyer_lindquist_carter_constant, boyer_lindquist_walker_penrose,
+};
+
+#[derive(Debug, Clone, Copy)]
+pub struct Schwarzschild {
+    rs: f64,
+}
+
+impl Schwarzschild {
+    pub fn new(rs: f64) -> Self {
+        Self { rs }
+    }
+
+    pub fn rs(&self) -> f64 {
+        self.rs
+    }
+}
+
+impl From<BlackHole> for Schwarzschild {
+    fn from(value: BlackHole) -> Self {
+        Self::new(value.radius())
+    }
+}
+
+impl Metric for Schwarzschild {
+    fn components(&self, position: SphericalCoords4D) -> MetricComponents {
+        let r = position.r();
+        let f = 1.0 - self.rs / r;
+        let r2 = r.powi(2);
+        [
+            [-f, 0., 0., 0.],
+            [0., 1.0 / f, 0., 0.],
+            [0., 0., r2, 0.],
+            [0., 0., 0., r2 * position.theta().sin().powi(2)],
+        ]
+    }
+
+    fn geodesic(&self, state: SphericalState4D) -> SphericalState4D {
+        let rs = self.rs;
+        let (sin_theta, cos_theta) = state.theta().sin_cos();
+        let altitude = (state.r() - rs).max(crate::DIV_EPSILON);
+
+        let d2t = -rs * state.dr() * state.dt() / (state.r() * altitude);
+
+        let d2r = {
+            let term1 = rs * altitude * state.dt().powi(2) / (2.0 * state.r().powi(3));
+            let term2 = rs / (2.0 * state.r() * altitude) * state.dr().powi(2);
+            let term3 = state.dtheta().powi(2) + (sin_theta * state.dphi()).powi(2);
+            -term1 + term2 + altitude * term3
+        };
+
+        let d2theta = {
+            let term1 = -2.0 * state.dr() * state.dtheta() / state.r();
+            let term2 = sin_theta * cos_theta * state.dphi().powi(2);
+            term1 + term2
+        };
+
+        let d2phi = {
+            let term1 = state.dr() / state.r();
+            let term2 = state.dtheta() * cos_theta / sin_theta;
+            -2.0 * state.dphi() * (term1 + term2)
+        };
+
+        SphericalState4D::spherical(
+            state.dt(),
+            state.dr(),
+            state.dtheta(),
+            state.dphi(),
+            d2t,
+            d2r,
+            d2theta,
+            d2phi,
+        )
+    }
+
+    fn renormalize(&self, state: SphericalState4D) -> SphericalState4D {
+        let r = state.r();
+        let r2 = r.powi(2);
+        let theta = state.theta();
+
+        let denom = (1.0 - self.rs / r).max(crate::DIV_EPSILON);
+
+        let num_part1 = (1.0 / denom) * state.dr().powi(2);
+        let num_part2 = r2 * state.dtheta().powi(2);
+        let num_part3 = r2 * (theta.sin() * state.dphi()).powi(2);
+
+        // Past-directed, rays are traced backwards in time from the camera
+        let dt = -f64::sqrt((num_part1 + num_part2 + num_part3) / denom);
+        SphericalState4D::spherical(
+            state.t(),
+            state.r(),
+            state.theta(),
+            state.phi(),
+            dt,
+            state.dr(),
+            state.dtheta(),
+            state.dphi(),
+        )
+    }
+
+    fn walker_penrose(
+        &self,
+        position: SphericalCoords4D,
+        k: [f64; 4],
+        f: [f64; 4],
+    ) -> Option<(f64, f64)> {
+        Some(boyer_lindquist_walker_penrose(0., position, k, f))
+    }
+
+    fn carter_constant(&self, position: SphericalCoords4D, u: [f64; 4]) -> Option<f64> {
+        let g = self.components(position);
+        Some(boyer_lindquist_carter_constant(0., position, &g, u))
+    }
+}
@@ -0,0 +1,285 @@
+use crate::metric::MetricComponents;
+use crate::{BlackHole, CartesianCoords3D, CartesianState3D, GeodesicState, Metric, Norm};
+
+// Motion of the camera. It aberrates the pixel directions and Doppler-shifts the received light.
+#[derive(Debug, Clone, Copy)]
+pub enum Observer {
+    // The camera of the metric, see Metric::observer_velocity: the zero angular momentum
+    // observer, which doesn't move relative to the static one outside of a spinning black hole.
+    // Same as Boost with no velocity.
+    Static,
+    // Rotating around the z axis with the given angular velocity dφ/dt
+    CircularOrbit(f64),
+    // Falling towards the black hole from rest at infinity without angular momentum, so dragged
+    // along by a spinning one
+    RadialInfall,
+    // Moving relative to the Static observer with the given velocity, in units of c along the
+    // world axes
+    Boost(CartesianCoords3D),
+    // Given coordinate 4-velocity, for a camera following its own trajectory
+    Velocity([f64; 4]),
+}
+
+impl Observer {
+    // Circular orbit at the Keplerian angular velocity of the given radius, prograde with the disk
+    pub fn keplerian_orbit(black_hole: BlackHole, radius: f64) -> Self {
+        Self::CircularOrbit(black_hole.accretion_disk().angular_velocity(radius))
+    }
+
+    // 4-velocity at the position of the state, normalised to u.u = -1. Observers can't move
+    // faster than MAX_OBSERVER_SPEED relative to the Static observer, and an orbit going faster
+    // than light falls back to it.
+    pub fn velocity<S: GeodesicState, M: Metric<S>>(&self, metric: &M, state: S) -> [f64; 4] {
+        let g = metric.components(state.coordinates());
+        let reference = metric.observer_velocity(state.coordinates());
+        match *self {
+            Self::Static => reference,
+            Self::CircularOrbit(angular_velocity) => {
+                let orbit = combine(
+                    [1., 0., 0., 0.],
+                    state.rotation_generator(),
+                    angular_velocity,
+                );
+                let norm = -dot(&g, orbit, orbit);
+                if norm <= crate::DIV_EPSILON {
+                    return reference;
+                }
+                scale(orbit, 1. / norm.sqrt())
+            }
+            Self::RadialInfall => radial_infall(&g, state).unwrap_or(reference),
+            Self::Boost(velocity) => boost(&g, state, reference, velocity),
+            Self::Velocity(velocity) => velocity,
+        }
+    }
+}
+
+pub(crate) fn lower(g: &MetricComponents, vector: [f64; 4]) -> [f64; 4] {
+    g.map(|row| row.iter().zip(vector).map(|(g, v)| g * v).sum())
+}
+
+pub(crate) fn dot(g: &MetricComponents, a: [f64; 4], b: [f64; 4]) -> f64 {
+    lower(g, a).iter().zip(b).map(|(a, b)| a * b).sum()
+}
+
+// a + factor b
+pub(crate) fn combine(a: [f64; 4], b: [f64; 4], factor: f64) -> [f64; 4] {
+    std::array::from_fn(|i| a[i] + factor * b[i])
+}
+
+pub(crate) fn scale(vector: [f64; 4], factor: f64) -> [f64; 4] {
+    vector.map(|v| v * factor)
+}
+
+// Coordinate components of a Cartesian direction at the position of the state
+pub(crate) fn coordinate_vector<S: GeodesicState>(
+    state: &S,
+    direction: CartesianCoords3D,
+) -> [f64; 4] {
+    let position = state.cartesian_position();
+    S::from_spatial(CartesianState3D::cartesian(
+        position.x(),
+        position.y(),
+        position.z(),
+        direction.x(),
+        direction.y(),
+        direction.z(),
+    ))
+    .velocity()
+}
+
+// Unit spacelike vector orthogonal to the observer u and to the given unit spacelike vectors
+pub(crate) fn orthonormalize(
+    g: &MetricComponents,
+    vector: [f64; 4],
+    u: [f64; 4],
+    others: &[[f64; 4]],
+) -> [f64; 4] {
+    let vector = combine(vector, u, dot(g, vector, u));
+    let vector = others.iter().fold(vector, |vector, &other| {
+        combine(vector, other, -dot(g, vector, other))
+    });
+    scale(
+        vector,
+        1. / dot(g, vector, vector).max(crate::DIV_EPSILON).sqrt(),
+    )
+}
+
+// 4-velocity of the zero angular momentum observer, normal to the hypersurfaces of constant
+// coordinate time: ∂t minus its projection on them, normalised. Unlike the observer at rest it
+// also exists inside the ergosphere. Where the hypersurfaces aren't spacelike, inside the horizon
+// of coordinates that don't cross it or on the polar axis, ∂t / sqrt(-g_tt) is returned instead.
+pub(crate) fn zamo_velocity(g: &MetricComponents) -> [f64; 4] {
+    let mut spatial: Vec<[f64; 4]> = Vec::with_capacity(3);
+    for i in 1..4 {
+        let mut axis = [0.; 4];
+        axis[i] = 1.;
+        let axis = spatial.iter().fold(axis, |axis, &other| {
+            combine(axis, other, -dot(g, axis, other))
+        });
+        let norm = dot(g, axis, axis);
+        if norm <= crate::DIV_EPSILON {
+            return [1. / (-g[0][0]).max(crate::DIV_EPSILON).sqrt(), 0., 0., 0.];
+        }
+        spatial.push(scale(axis, 1. / norm.sqrt()));
+    }
+    let normal = spatial.iter().fold([1., 0., 0., 0.], |normal, &axis| {
+        combine(normal, axis, -dot(g, normal, axis))
+    });
+    let lapse = -dot(g, normal, normal);
+    if lapse <= crate::DIV_EPSILON {
+        return [1. / (-g[0][0]).max(crate::DIV_EPSILON).sqrt(), 0., 0., 0.];
+    }
+    scale(normal, 1. / lapse.sqrt())
+}
+
+// 4-velocity of a particle falling from rest at infinity without angular momentum, E = -u.∂t = 1
+// and L = u.∂φ = 0, moving in the plane of ∂t, ∂φ and the radial direction. None where there is
+// no such particle.
+fn radial_infall<S: GeodesicState>(g: &MetricComponents, state: S) -> Option<[f64; 4]> {
+    let time = [1., 0., 0., 0.];
+    let rotation = state.rotation_generator();
+    let radial = coordinate_vector(&state, state.cartesian_position().normalize());
+
+    // u = A ∂t + B ∂φ + C radial, the two conservation laws giving A and B for each C
+    let (g_tt, g_tφ, g_φφ) = (
+        dot(g, time, time),
+        dot(g, time, rotation),
+        dot(g, rotation, rotation),
+    );
+    let determinant = g_tt * g_φφ - g_tφ.powi(2);
+    let solve = |energy: f64, momentum: f64| -> [f64; 4] {
+        if determinant.abs() <= crate::DIV_EPSILON {
+            // On the polar axis, where ∂φ vanishes
+            return scale(time, energy / g_tt);
+        }
+        let a = (energy * g_φφ - momentum * g_tφ) / determinant;
+        let b = (momentum * g_tt - energy * g_tφ) / determinant;
+        combine(scale(time, a), rotation, b)
+    };
+    let constant = solve(-1., 0.);
+    let slope = combine(
+        radial,
+        solve(-dot(g, time, radial), -dot(g, rotation, radial)),
+        1.,
+    );
+
+    // Normalisation, (constant + C slope)² = -1, with the inward root C < 0
+    let a = dot(g, slope, slope);
+    let b = dot(g, constant, slope);
+    let c = dot(g, constant, constant) + 1.;
+    let discriminant = b.powi(2) - a * c;
+    if a <= crate::DIV_EPSILON || discriminant < 0. {
+        return None;
+    }
+    let root = (-b - discriminant.sqrt()) / a;
+    Some(combine(constant, slope, root))
+}
+
+// Orthonormal tetrad of the observer u at the position of the state: u, then the world x, y and
+// z axes. Gram-Schmidt runs on the radial, polar and azimuthal directions, which the spherical
+// metrics keep orthogonal, so that the frame isn't sheared, and they are rotated back to the
+// world axes afterwards.
+pub(crate) fn tetrad<S: GeodesicState>(
+    g: &MetricComponents,
+    state: S,
+    u: [f64; 4],
+) -> [[f64; 4]; 4] {
+    let radial = state.cartesian_position().normalize();
+    let mut azimuthal = CartesianCoords3D::cartesian(0., 0., 1.).cross(radial);
+    if azimuthal.norm() < crate::DIV_EPSILON {
+        azimuthal = CartesianCoords3D::cartesian(0., 1., 0.);
+    }
+    let azimuthal = azimuthal.normalize();
+    let polar = azimuthal.cross(radial);
+
+    let e_radial = orthonormalize(g, coordinate_vector(&state, radial), u, &[]);
+    let e_polar = orthonormalize(g, coordinate_vector(&state, polar), u, &[e_radial]);
+    let e_azimuthal = orthonormalize(
+        g,
+        coordinate_vector(&state, azimuthal),
+        u,
+        &[e_radial, e_polar],
+    );
+
+    let axis = |x, y, z| {
+        let world = CartesianCoords3D::cartesian(x, y, z);
+        [
+            (radial, e_radial),
+            (polar, e_polar),
+            (azimuthal, e_azimuthal),
+        ]
+        .iter()
+        .fold([0.; 4], |axis, &(direction, e)| {
+            combine(axis, e, world.dot(direction))
+        })
+    };
+    [u, axis(1., 0., 0.), axis(0., 1., 0.), axis(0., 0., 1.)]
+}
+
+// u = γ (e_0 + v^i e_i) in the tetrad of the reference observer
+fn boost<S: GeodesicState>(
+    g: &MetricComponents,
+    state: S,
+    reference: [f64; 4],
+    velocity: CartesianCoords3D,
+) -> [f64; 4] {
+    let speed = velocity.norm();
+    let velocity = if speed > crate::MAX_OBSERVER_SPEED {
+        velocity * (crate::MAX_OBSERVER_SPEED / speed)
+    } else {
+        velocity
+    };
+    let lorentz_factor = 1. / (1. - velocity.norm().powi(2)).sqrt();
+
+    let [e0, x, y, z] = tetrad(g, state, reference);
+    let spatial = combine(
+        combine(scale(x, velocity.x()), y, velocity.y()),
+        z,
+        velocity.z(),
+    );
+    scale(combine(e0, spatial, 1.), lorentz_factor)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CartesianState4D, Kerr, KerrSchild, SphericalCoords4D};
+    use std::f64::consts::PI;
+
+    #[test]
+    fn zamo_has_no_angular_momentum_and_turns_with_the_frame_dragging() {
+        // a = 0.9 M: outside the ergosphere, inside it at the equator (r < 2M) and close to the
+        // horizon at r+ = 1.44 M
+        let (mass, a) = (1., 0.9);
+        let metric = Kerr::new(2. * mass, a);
+        for (r, theta) in [(10., 1.), (3., 0.3), (1.8, 0.5 * PI), (1.5, 0.5 * PI)] {
+            let g = metric.components(SphericalCoords4D::spherical(0., r, theta, 0.));
+            let u = zamo_velocity(&g);
+            assert!((dot(&g, u, u) + 1.).abs() < 1e-9, "u.u = {}", dot(&g, u, u));
+            let angular_momentum = lower(&g, u)[3];
+            assert!(angular_momentum.abs() < 1e-9, "u_φ = {angular_momentum}");
+
+            // ω = dφ/dt = 2Mar / ((r² + a²)² - a²Δ sin²θ)
+            let delta = r.powi(2) - 2. * mass * r + a.powi(2);
+            let expected = 2. * mass * a * r
+                / ((r.powi(2) + a.powi(2)).powi(2) - (a * theta.sin()).powi(2) * delta);
+            let omega = u[3] / u[0];
+            assert!(
+                (omega - expected).abs() < 1e-9 * expected,
+                "ω = {omega}, expected {expected}"
+            );
+        }
+
+        // Kerr-Schild coordinates, where it also exists inside the horizon
+        let metric = KerrSchild::new(2. * mass, a);
+        for (x, y, z) in [(5., 2., 1.), (1., 0.5, 0.2)] {
+            let state =
+                CartesianState4D::from_spatial(CartesianState3D::cartesian(x, y, z, 0., 0., 0.));
+            let g = metric.components(state.coordinates());
+            let u = zamo_velocity(&g);
+            assert!((dot(&g, u, u) + 1.).abs() < 1e-9, "u.u = {}", dot(&g, u, u));
+            let angular_momentum = dot(&g, u, state.rotation_generator());
+            assert!(angular_momentum.abs() < 1e-9, "u.∂φ = {angular_momentum}");
+        }
+    }
+}
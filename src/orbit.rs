@@ -0,0 +1,469 @@
+use std::f64::consts::PI;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::Observer;
+use crate::observer;
+use crate::{BlackHole, CartesianCoords3D, CartesianState3D, GeodesicState, Metric, Norm};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Particle {
+    // Normalised with u.u = -1, the affine parameter is the proper time
+    Massive,
+    // Light, u.u = 0
+    Massless,
+}
+
+impl Particle {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Massive => "massive",
+            Self::Massless => "massless",
+        }
+    }
+}
+
+// Why the integration stopped
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OrbitEnd {
+    Captured,
+    Escaped,
+    ReachedDuration,
+    IntegrationFailed,
+}
+
+impl OrbitEnd {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Captured => "captured",
+            Self::Escaped => "escaped",
+            Self::ReachedDuration => "reached_duration",
+            Self::IntegrationFailed => "integration_failed",
+        }
+    }
+}
+
+// State after an accepted integration step. Energy and angular momentum around the z axis are
+// -u_t and u_φ, per unit mass for massive particles.
+#[derive(Debug, Clone, Copy)]
+pub struct OrbitPoint {
+    affine_parameter: f64,
+    coordinate_time: f64,
+    position: CartesianCoords3D,
+    swept_angle: f64, // around the black hole since the start, always increasing
+    energy: f64,
+    angular_momentum: f64,
+    carter_constant: Option<f64>,
+    norm: f64, // u.u, drifts away from -1 or 0 with the integration error
+}
+
+impl OrbitPoint {
+    pub fn affine_parameter(&self) -> f64 {
+        self.affine_parameter
+    }
+    pub fn coordinate_time(&self) -> f64 {
+        self.coordinate_time
+    }
+    pub fn position(&self) -> CartesianCoords3D {
+        self.position
+    }
+    pub fn radius(&self) -> f64 {
+        self.position.norm()
+    }
+    pub fn swept_angle(&self) -> f64 {
+        self.swept_angle
+    }
+    pub fn energy(&self) -> f64 {
+        self.energy
+    }
+    pub fn angular_momentum(&self) -> f64 {
+        self.angular_momentum
+    }
+    pub fn carter_constant(&self) -> Option<f64> {
+        self.carter_constant
+    }
+    pub fn norm(&self) -> f64 {
+        self.norm
+    }
+}
+
+// Numbers derived from the radial turning points of a bound orbit. Periods and precession are
+// averaged over every full radial oscillation, which needs at least two periapses.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OrbitSummary {
+    periapsis: Option<f64>,
+    apoapsis: Option<f64>,
+    radial_period: Option<f64>, // in coordinate time
+    precession: Option<f64>,    // advance of the periapsis per radial period, in radians
+    weak_field_precession: Option<f64>,
+}
+
+impl OrbitSummary {
+    pub fn periapsis(&self) -> Option<f64> {
+        self.periapsis
+    }
+    pub fn apoapsis(&self) -> Option<f64> {
+        self.apoapsis
+    }
+    pub fn radial_period(&self) -> Option<f64> {
+        self.radial_period
+    }
+    pub fn precession(&self) -> Option<f64> {
+        self.precession
+    }
+    // 6πM / (a (1 - e²)) of Schwarzschild to first order in M / r, for the same turning points
+    pub fn weak_field_precession(&self) -> Option<f64> {
+        self.weak_field_precession
+    }
+}
+
+// Trajectory of a free particle, integrated with solve_geodesic_rkf45 from an initial position
+// and velocity, forward in time
+#[derive(Debug, Clone)]
+pub struct Orbit {
+    particle: Particle,
+    mass: f64, // of the black hole
+    points: Vec<OrbitPoint>,
+    end: OrbitEnd,
+}
+
+impl Orbit {
+    // The velocity is relative to the zero angular momentum observer at the position, see
+    // Observer::Static, in units of c. Only its direction matters for light. Integrates until
+    // the coordinate time reaches the duration, or the particle falls into the hole or leaves
+    // the bounding box.
+    // The metric M is built from the black hole.
+    pub fn integrate<S: GeodesicState, M: Metric<S> + From<BlackHole>>(
+        black_hole: BlackHole,
+        particle: Particle,
+        position: CartesianCoords3D,
+        velocity: CartesianCoords3D,
+        duration: f64,
+    ) -> Self {
+        let metric = M::from(black_hole);
+        let mut state = initial_state(&metric, particle, position, velocity);
+        let rs = black_hole.radius();
+        let bounding_box_radius = rs * crate::BOUNDING_BOX_FACTOR;
+
+        let mut affine_parameter = 0.;
+        let mut swept_angle = 0.;
+        let mut points = vec![point(&metric, state, affine_parameter, swept_angle)];
+        let mut dλ = rs * crate::INTEGRATION_STEP_FACTOR;
+        let end = loop {
+            if has_fallen(&metric, black_hole, state) {
+                break OrbitEnd::Captured;
+            }
+            if metric.radius(state) > bounding_box_radius && state.is_receding() {
+                break OrbitEnd::Escaped;
+            }
+            if state.time() >= duration {
+                break OrbitEnd::ReachedDuration;
+            }
+            if points.len() > crate::TRAJECTORY_MAX_STEPS {
+                break OrbitEnd::IntegrationFailed;
+            }
+
+            let step = dλ.min(metric.radius(state) * crate::TRAJECTORY_STEP_FACTOR);
+            let Ok((next, taken, next_dλ)) =
+                crate::geodesic::solve_geodesic_rkf45_with_step(state, &metric, rs, step)
+            else {
+                break OrbitEnd::IntegrationFailed;
+            };
+            let next = renormalize(&metric, particle, next);
+
+            let (position1, position2) = (
+                metric.cartesian_position(state),
+                metric.cartesian_position(next),
+            );
+            let cos_angle = position1.normalize().dot(position2.normalize());
+            swept_angle += cos_angle.clamp(-1., 1.).acos();
+            affine_parameter += taken;
+            state = next;
+            dλ = next_dλ;
+            points.push(point(&metric, state, affine_parameter, swept_angle));
+        };
+
+        Self {
+            particle,
+            mass: black_hole.mass(),
+            points,
+            end,
+        }
+    }
+
+    pub fn particle(&self) -> Particle {
+        self.particle
+    }
+    pub fn points(&self) -> &[OrbitPoint] {
+        &self.points
+    }
+    pub fn end(&self) -> OrbitEnd {
+        self.end
+    }
+
+    // Proper time of the particle at the point, zero for light
+    pub fn proper_time(&self, point: &OrbitPoint) -> f64 {
+        match self.particle {
+            Particle::Massive => point.affine_parameter,
+            Particle::Massless => 0.,
+        }
+    }
+
+    // Refined turning points, as (swept angle, coordinate time, radius), where the radius is
+    // smallest for periapses and largest for apoapses
+    fn turning_points(&self, periapses: bool) -> Vec<(f64, f64, f64)> {
+        let sign = if periapses { 1. } else { -1. };
+        self.points
+            .windows(3)
+            .filter_map(|points| {
+                let [r0, r1, r2] = [0, 1, 2].map(|i| sign * points[i].radius());
+                if !(r1 < r0 && r1 <= r2) {
+                    return None;
+                }
+                // Vertex of the parabola through the three points, as an offset from the middle
+                let curvature = r0 - 2. * r1 + r2;
+                let offset = (0.5 * (r0 - r2) / curvature.max(crate::DIV_EPSILON)).clamp(-1., 1.);
+                let radius = sign * (r1 - 0.25 * (r0 - r2) * offset);
+                let (a, b) = if offset < 0. {
+                    (points[1], points[0])
+                } else {
+                    (points[1], points[2])
+                };
+                let interpolate =
+                    |f: fn(&OrbitPoint) -> f64| f(&a) + offset.abs() * (f(&b) - f(&a));
+                Some((
+                    interpolate(OrbitPoint::swept_angle),
+                    interpolate(OrbitPoint::coordinate_time),
+                    radius,
+                ))
+            })
+            .collect()
+    }
+
+    pub fn summary(&self) -> OrbitSummary {
+        let periapses = self.turning_points(true);
+        let apoapses = self.turning_points(false);
+        let periapsis = periapses.iter().map(|&(_, _, r)| r).reduce(f64::min);
+        let apoapsis = apoapses.iter().map(|&(_, _, r)| r).reduce(f64::max);
+
+        // Integration noise makes turning points on circular orbits
+        let circular = periapsis.zip(apoapsis).is_none_or(|(periapsis, apoapsis)| {
+            apoapsis - periapsis < apoapsis * crate::TRAJECTORY_CIRCULAR_TOLERANCE
+        });
+        let (radial_period, precession) = match (periapses.first(), periapses.last()) {
+            (Some(first), Some(last)) if periapses.len() >= 2 && !circular => {
+                let orbits = (periapses.len() - 1) as f64;
+                (
+                    Some((last.1 - first.1) / orbits),
+                    Some((last.0 - first.0) / orbits - 2. * PI),
+                )
+            }
+            _ => (None, None),
+        };
+        let weak_field_precession =
+            periapsis
+                .zip(apoapsis)
+                .filter(|_| !circular)
+                .map(|(periapsis, apoapsis)| {
+                    let semi_latus_rectum = 2. * periapsis * apoapsis / (periapsis + apoapsis);
+                    6. * PI * self.mass / semi_latus_rectum
+                });
+
+        OrbitSummary {
+            periapsis,
+            apoapsis,
+            radial_period,
+            precession,
+            weak_field_precession,
+        }
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from(
+            "step,affine_parameter,proper_time,coordinate_time,x,y,z,r,swept_angle,energy,angular_momentum,carter_constant,norm\n",
+        );
+        for (step, point) in self.points.iter().enumerate() {
+            csv.push_str(&format!(
+                "{step},{},{},{},{},{},{},{},{},{},{},{},{}\n",
+                point.affine_parameter,
+                self.proper_time(point),
+                point.coordinate_time,
+                point.position.x(),
+                point.position.y(),
+                point.position.z(),
+                point.radius(),
+                point.swept_angle,
+                point.energy,
+                point.angular_momentum,
+                point
+                    .carter_constant
+                    .map_or(String::new(), |q| q.to_string()),
+                point.norm,
+            ));
+        }
+        csv
+    }
+
+    pub fn to_json(&self) -> String {
+        let summary = self.summary();
+        let points: Vec<String> = self
+            .points
+            .iter()
+            .map(|point| {
+                format!(
+                    "{{\"affine_parameter\":{},\"proper_time\":{},\"coordinate_time\":{},\"x\":{},\"y\":{},\"z\":{},\"r\":{},\"swept_angle\":{},\"energy\":{},\"angular_momentum\":{},\"carter_constant\":{},\"norm\":{}}}",
+                    json_number(Some(point.affine_parameter)),
+                    json_number(Some(self.proper_time(point))),
+                    json_number(Some(point.coordinate_time)),
+                    json_number(Some(point.position.x())),
+                    json_number(Some(point.position.y())),
+                    json_number(Some(point.position.z())),
+                    json_number(Some(point.radius())),
+                    json_number(Some(point.swept_angle)),
+                    json_number(Some(point.energy)),
+                    json_number(Some(point.angular_momentum)),
+                    json_number(point.carter_constant),
+                    json_number(Some(point.norm)),
+                )
+            })
+            .collect();
+        format!(
+            "{{\"particle\":\"{}\",\"end\":\"{}\",\"summary\":{{\"periapsis\":{},\"apoapsis\":{},\"radial_period\":{},\"precession\":{},\"weak_field_precession\":{}}},\"points\":[\n{}\n]}}\n",
+            self.particle.name(),
+            self.end.name(),
+            json_number(summary.periapsis),
+            json_number(summary.apoapsis),
+            json_number(summary.radial_period),
+            json_number(summary.precession),
+            json_number(summary.weak_field_precession),
+            points.join(",\n"),
+        )
+    }
+
+    // Writes to_json for a .json path and to_csv otherwise
+    pub fn export(&self, path: &Path) -> io::Result<()> {
+        let contents = match path.extension().and_then(|extension| extension.to_str()) {
+            Some("json") => self.to_json(),
+            _ => self.to_csv(),
+        };
+        fs::write(path, contents)
+    }
+}
+
+// Non-finite numbers aren't valid JSON
+pub(crate) fn json_number(value: Option<f64>) -> String {
+    match value {
+        Some(value) if value.is_finite() => value.to_string(),
+        _ => String::from("null"),
+    }
+}
+
+fn initial_state<S: GeodesicState, M: Metric<S>>(
+    metric: &M,
+    particle: Particle,
+    position: CartesianCoords3D,
+    velocity: CartesianCoords3D,
+) -> S {
+    let state = S::from_spatial(CartesianState3D::cartesian(
+        position.x(),
+        position.y(),
+        position.z(),
+        0.,
+        0.,
+        0.,
+    ));
+    match particle {
+        Particle::Massive => state.with_velocity(Observer::Boost(velocity).velocity(metric, state)),
+        Particle::Massless => {
+            // k = u + n in the tetrad of the zero angular momentum observer, with n the direction
+            // of motion
+            let g = metric.components(state.coordinates());
+            let u = Observer::Static.velocity(metric, state);
+            let [_, x, y, z] = observer::tetrad(&g, state, u);
+            let direction = velocity.normalize();
+            let n = observer::combine(
+                observer::combine(observer::scale(x, direction.x()), y, direction.y()),
+                z,
+                direction.z(),
+            );
+            state.with_velocity(observer::combine(u, n, 1.))
+        }
+    }
+}
+
+fn renormalize<S: GeodesicState, M: Metric<S>>(metric: &M, particle: Particle, state: S) -> S {
+    match particle {
+        Particle::Massive => metric.renormalize_timelike(state),
+        Particle::Massless => {
+            // renormalize keeps the past-directed root of the traced rays
+            let reverse = |state: S| state.with_velocity(state.velocity().map(|v| -v));
+            reverse(metric.renormalize(reverse(state)))
+        }
+    }
+}
+
+fn has_fallen<S: GeodesicState, M: Metric<S>>(metric: &M, black_hole: BlackHole, state: S) -> bool {
+    let r = metric.radius(state);
+    if metric.is_horizon_penetrating() {
+        r <= black_hole.radius() * crate::SINGULARITY_RADIUS_FACTOR
+    } else {
+        r <= black_hole.visual_radius()
+    }
+}
+
+fn point<S: GeodesicState, M: Metric<S>>(
+    metric: &M,
+    state: S,
+    affine_parameter: f64,
+    swept_angle: f64,
+) -> OrbitPoint {
+    let g = metric.components(state.coordinates());
+    let u = state.velocity();
+    let momentum = observer::lower(&g, u);
+    OrbitPoint {
+        affine_parameter,
+        coordinate_time: state.time(),
+        position: metric.cartesian_position(state),
+        swept_angle,
+        energy: -momentum[0],
+        angular_momentum: momentum
+            .iter()
+            .zip(state.rotation_generator())
+            .map(|(p, xi)| p * xi)
+            .sum(),
+        carter_constant: metric.carter_constant(state.coordinates(), u),
+        norm: observer::dot(&g, u, u),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CartesianCoords4D, Schwarzschild};
+
+    #[test]
+    fn weak_field_precession_matches_schwarzschild() {
+        // Bound orbit between r = 300 M and r = 600 M, where corrections to 6πM / p are of
+        // order M / p
+        let black_hole = BlackHole::new(CartesianCoords4D::cartesian(0., 0., 0., 0.), 1.);
+        let (apoapsis, periapsis): (f64, f64) = (600., 300.);
+        let speed = (2. * periapsis / (apoapsis * (apoapsis + periapsis))).sqrt();
+        let orbit = Orbit::integrate::<_, Schwarzschild>(
+            black_hole,
+            Particle::Massive,
+            CartesianCoords3D::cartesian(apoapsis, 0., 0.),
+            CartesianCoords3D::cartesian(0., speed, 0.),
+            150_000.,
+        );
+        assert_eq!(orbit.end(), OrbitEnd::ReachedDuration);
+
+        let summary = orbit.summary();
+        let precession = summary.precession().expect("two periapses");
+        let expected = summary.weak_field_precession().expect("a bound orbit");
+        assert!(
+            (precession - expected).abs() < 0.03 * expected,
+            "precession {precession}, expected {expected}"
+        );
+    }
+}
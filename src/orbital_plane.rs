@@ -0,0 +1,229 @@
+use macroquad::prelude::*;
+use std::sync::Arc;
+
+use crate::black_hole::DiskCrossing;
+use crate::ray::{Photon, StoppingCriterion, blend, determine_color, gamma_correct};
+use crate::{BlackHole, CartesianCoords3D, CartesianState3D, Norm, Skybox, Tensor2D};
+
+// Orthonormal basis of the plane spanned by the position and the direction of a ray,
+// e1 points from the black hole to the position and e2 along the tangential motion
+pub(crate) fn orbital_plane_basis(
+    position: CartesianCoords3D,
+    direction: CartesianCoords3D,
+) -> (CartesianCoords3D, CartesianCoords3D) {
+    let e1 = position.normalize();
+
+    let tangent = direction - e1 * direction.dot(e1);
+    let e2 = if tangent.norm() > crate::DIV_EPSILON {
+        tangent.normalize()
+    } else {
+        // Radial ray, any plane containing it works
+        let helper = if e1.z().abs() < 0.9 {
+            CartesianCoords3D::cartesian(0., 0., 1.)
+        } else {
+            CartesianCoords3D::cartesian(1., 0., 0.)
+        };
+        e1.cross(helper).cross(e1).normalize()
+    };
+    (e1, e2)
+}
+
+// Fast path for Schwarzschild: a null geodesic stays in the plane spanned by the camera
+// position and the ray direction, so it reduces to the 1D Binet equation in w = rs / r.
+// Only valid for non-rotating, uncharged black holes with a thin disk.
+pub struct PlanarRay {
+    // Orthonormal basis of the orbital plane, e1 points from the black hole to the camera
+    e1: CartesianCoords3D,
+    e2: CartesianCoords3D,
+    // (w, dw/dψ)
+    state: Tensor2D,
+    psi: f64,
+    dψ: f64,
+    rs: f64,
+    photon: Photon,
+    // Number of times the ray went through the equatorial plane
+    crossings: usize,
+}
+
+impl PlanarRay {
+    pub fn new(spatial_state: CartesianState3D, rs: f64) -> Self {
+        let position = spatial_state.position();
+        let direction = CartesianCoords3D::cartesian(
+            spatial_state.dx(),
+            spatial_state.dy(),
+            spatial_state.dz(),
+        );
+        let r = position.norm();
+        let (e1, e2) = orbital_plane_basis(position, direction);
+
+        let radial_velocity = direction.dot(e1);
+        let angular_velocity = direction.dot(e2).max(crate::DIV_EPSILON);
+
+        // dr/dψ = r v_r / v_ψ, hence dw/dψ = -w v_r / v_ψ
+        let w = rs / r;
+        let dw = -w * radial_velocity / angular_velocity;
+
+        // Impact parameter from (dw/dψ)² + w² (1 - w) = (rs / b)². The photon moves along -e2 at the camera.
+        let impact_parameter = rs / (dw.powi(2) + w.powi(2) * (1. - w)).sqrt();
+        let camera_energy = 1. / (1. - w).sqrt();
+        let photon = Photon::new(
+            1. / camera_energy,
+            -impact_parameter * e1.cross(e2).z() / camera_energy,
+        );
+
+        Self {
+            e1,
+            e2,
+            state: Tensor2D::new(w, dw),
+            psi: 0.,
+            dψ: crate::ORBIT_INITIAL_STEP,
+            rs,
+            photon,
+            crossings: 0,
+        }
+    }
+
+    // Rotates the in-plane polar position back to world space
+    fn world_position(&self, w: f64, psi: f64) -> CartesianCoords3D {
+        let (sin_psi, cos_psi) = psi.sin_cos();
+        (self.e1 * cos_psi + self.e2 * sin_psi) * (self.rs / w.max(crate::DIV_EPSILON))
+    }
+
+    pub fn step(
+        &mut self,
+        black_hole: BlackHole,
+        bounding_box_radius: f64,
+    ) -> Option<StoppingCriterion> {
+        let (w, dw) = self.state.unpack();
+        if w >= self.rs / black_hole.visual_radius() {
+            return Some(StoppingCriterion::EnteredEventHorizon);
+        }
+
+        let (state, taken, dψ) = match crate::geodesic::solve_orbit_rkf45(self.state, self.dψ) {
+            Ok(step) => step,
+            // Light moving inwards inside the photon sphere, r = 1.5 rs, has no turning point
+            // left and falls into the black hole, like close to it in Ray::step
+            Err(_) if w > 2. / 3. && dw > 0. => {
+                return Some(StoppingCriterion::EnteredEventHorizon);
+            }
+            Err(_) => return Some(StoppingCriterion::IntegrationFailed),
+        };
+        // A retried step is shorter than the one asked for
+        let psi = self.psi + taken;
+
+        let position = self.world_position(w, self.psi);
+        let next_position = self.world_position(state.a, psi);
+
+        let w_bounding_box = self.rs / bounding_box_radius;
+        if state.a <= w_bounding_box && dw < 0. {
+            // We are very far from the black hole AND we are moving away from it.
+            // Large steps can overshoot far past the bounding sphere, so interpolate back onto it.
+            let fraction = ((w - w_bounding_box) / (w - state.a)).clamp(0., 1.);
+            let exit_position = self.world_position(w_bounding_box, self.psi + fraction * taken);
+            return Some(StoppingCriterion::OutOfBoundingBox(exit_position));
+        }
+
+        self.state = state;
+        self.psi = psi;
+        self.dψ = dψ;
+
+        let order = self.crossings;
+        if (position.z() < 0.) != (next_position.z() < 0.) {
+            self.crossings += 1;
+        }
+
+        // Check if we crossed accretion disk.
+        // Coordinate time isn't integrated, the disk is seen as it is at the camera time.
+        if let Some((crossing, _)) = black_hole
+            .accretion_disk()
+            .check_intersection(position, next_position)
+        {
+            let cos_incidence = (next_position - position).normalize().z().abs();
+            return Some(StoppingCriterion::CrossedAccretionDisk(
+                DiskCrossing::new(crossing, 0., cos_incidence, order),
+                self.photon,
+            ));
+        };
+
+        None
+    }
+
+    pub fn get_color(
+        &mut self,
+        black_hole: BlackHole,
+        bounding_box_radius: f64,
+        skybox: Arc<Skybox>,
+    ) -> Color {
+        let mut accumulated_color = Color::new(0.0, 0.0, 0.0, 0.0);
+        let mut transmittance = 1.0;
+
+        for _ in 0..crate::NUM_INTEGRATION_STEPS {
+            if let Some(criterion) = self.step(black_hole, bounding_box_radius) {
+                let hit_color = determine_color(&criterion, black_hole, &skybox);
+                (accumulated_color, transmittance) =
+                    blend(accumulated_color, hit_color, transmittance);
+                if transmittance < 0.05 {
+                    break;
+                }
+            }
+        }
+
+        let (final_color, _) = blend(accumulated_color, crate::BACKGROUND_COLOR, transmittance);
+
+        gamma_correct(final_color)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CartesianCoords4D, Ray, Schwarzschild};
+    use image::{DynamicImage, Rgb, Rgb32FImage};
+
+    // Sky whose colour changes smoothly with the direction, so that rays leaving in nearly the
+    // same direction get nearly the same colour
+    fn gradient_skybox() -> Arc<Skybox> {
+        let image = Rgb32FImage::from_fn(64, 32, |x, y| Rgb([x as f32 / 64., y as f32 / 32., 0.5]));
+        Arc::new(Skybox::from_image(DynamicImage::ImageRgb32F(image)))
+    }
+
+    #[test]
+    fn planar_ray_matches_ray_in_schwarzschild() {
+        let black_hole = BlackHole::new(CartesianCoords4D::cartesian(0., 0., 0., 0.), 1.);
+        let rs = black_hole.radius();
+        let metric = Schwarzschild::from(black_hole);
+        let bounding_box_radius = rs * crate::BOUNDING_BOX_FACTOR;
+        let skybox = gradient_skybox();
+        let camera = CartesianCoords3D::cartesian(-40., 0., 6.);
+
+        // Into the shadow, onto the disk in front of the hole and around it, and out to the sky
+        for (dy, dz) in [(0., 0.), (0., -0.1), (0.05, 0.08), (0.3, 0.1), (-0.5, 0.3)] {
+            let direction = (camera * -1.).normalize() + CartesianCoords3D::cartesian(0., dy, dz);
+            let spatial_state = CartesianState3D::cartesian(
+                camera.x(),
+                camera.y(),
+                camera.z(),
+                direction.x(),
+                direction.y(),
+                direction.z(),
+            );
+            let expected = Ray::new(spatial_state, metric, rs * crate::INTEGRATION_STEP_FACTOR)
+                .get_color(black_hole, bounding_box_radius, Arc::clone(&skybox));
+            let actual = PlanarRay::new(spatial_state, rs).get_color(
+                black_hole,
+                bounding_box_radius,
+                Arc::clone(&skybox),
+            );
+            for (e, a) in [
+                (expected.r, actual.r),
+                (expected.g, actual.g),
+                (expected.b, actual.b),
+            ] {
+                assert!(
+                    (e - a).abs() < 0.02,
+                    "expected {expected:?}, got {actual:?}"
+                );
+            }
+        }
+    }
+}
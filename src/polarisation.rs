@@ -0,0 +1,340 @@
+use std::ops::{Add, Mul};
+
+use crate::metric::MetricComponents;
+use crate::observer::{combine, coordinate_vector, dot, lower, orthonormalize, scale};
+use crate::{CartesianCoords3D, GeodesicState, Metric, Norm};
+
+// Large-scale geometry of the magnetic field threading the disk, as weights of the radial,
+// vertical and toroidal directions in the frame of the gas. Only the direction matters.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MagneticField {
+    radial: f64,
+    vertical: f64,
+    toroidal: f64,
+}
+
+impl MagneticField {
+    pub const RADIAL: Self = Self::new(1., 0., 0.);
+    pub const VERTICAL: Self = Self::new(0., 1., 0.);
+    pub const TOROIDAL: Self = Self::new(0., 0., 1.);
+
+    pub const fn new(radial: f64, vertical: f64, toroidal: f64) -> Self {
+        Self {
+            radial,
+            vertical,
+            toroidal,
+        }
+    }
+
+    pub fn radial(&self) -> f64 {
+        self.radial
+    }
+    pub fn vertical(&self) -> f64 {
+        self.vertical
+    }
+    pub fn toroidal(&self) -> f64 {
+        self.toroidal
+    }
+
+    // Direction of the field at the given position, before it is projected in the frame of the gas
+    pub fn direction(&self, position: CartesianCoords3D) -> CartesianCoords3D {
+        let cylindrical_radius = position.x().hypot(position.y()).max(crate::DIV_EPSILON);
+        let (cos_phi, sin_phi) = (
+            position.x() / cylindrical_radius,
+            position.y() / cylindrical_radius,
+        );
+        CartesianCoords3D::cartesian(
+            self.radial * cos_phi - self.toroidal * sin_phi,
+            self.radial * sin_phi + self.toroidal * cos_phi,
+            self.vertical,
+        )
+    }
+}
+
+// Stokes parameters of the light reaching a pixel, in units of linear luminance. Q > 0 is
+// polarised along the projected z axis, U > 0 rotated by 45° from it towards the right of the
+// image, and V is circular polarisation.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Stokes {
+    i: f64,
+    q: f64,
+    u: f64,
+    v: f64,
+}
+
+impl Stokes {
+    pub fn new(i: f64, q: f64, u: f64, v: f64) -> Self {
+        Self { i, q, u, v }
+    }
+
+    // Partially linearly polarised light, the angle is measured from the projected z axis
+    pub fn linear(intensity: f64, fraction: f64, angle: f64) -> Self {
+        let polarised = intensity * fraction;
+        Self::new(
+            intensity,
+            polarised * (2. * angle).cos(),
+            polarised * (2. * angle).sin(),
+            0.,
+        )
+    }
+
+    pub fn unpolarised(intensity: f64) -> Self {
+        Self::new(intensity, 0., 0., 0.)
+    }
+
+    pub fn i(&self) -> f64 {
+        self.i
+    }
+    pub fn q(&self) -> f64 {
+        self.q
+    }
+    pub fn u(&self) -> f64 {
+        self.u
+    }
+    pub fn v(&self) -> f64 {
+        self.v
+    }
+
+    pub fn polarised_intensity(&self) -> f64 {
+        (self.q.powi(2) + self.u.powi(2) + self.v.powi(2)).sqrt()
+    }
+
+    // Electric vector position angle, from the projected z axis towards the right of the image
+    pub fn angle(&self) -> f64 {
+        0.5 * self.u.atan2(self.q)
+    }
+}
+
+impl Add for Stokes {
+    type Output = Stokes;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self::new(
+            self.i + rhs.i,
+            self.q + rhs.q,
+            self.u + rhs.u,
+            self.v + rhs.v,
+        )
+    }
+}
+
+impl Mul<f64> for Stokes {
+    type Output = Stokes;
+
+    fn mul(self, rhs: f64) -> Self::Output {
+        Self::new(self.i * rhs, self.q * rhs, self.u * rhs, self.v * rhs)
+    }
+}
+
+// Sign of the permutation (i, j, k, l) of (0, 1, 2, 3), 0 if an index repeats
+fn permutation_sign(indices: [usize; 4]) -> f64 {
+    let mut sign = 1.;
+    for a in 0..4 {
+        for b in a + 1..4 {
+            if indices[a] == indices[b] {
+                return 0.;
+            }
+            if indices[a] > indices[b] {
+                sign = -sign;
+            }
+        }
+    }
+    sign
+}
+
+fn determinant(g: &MetricComponents) -> f64 {
+    let mut total = 0.;
+    for i in 0..4 {
+        for j in 0..4 {
+            for k in 0..4 {
+                for l in 0..4 {
+                    let sign = permutation_sign([i, j, k, l]);
+                    if sign != 0. {
+                        total += sign * g[0][i] * g[1][j] * g[2][k] * g[3][l];
+                    }
+                }
+            }
+        }
+    }
+    total
+}
+
+// f^μ = ε^μνρσ a_ν b_ρ c_σ, the vector orthogonal to a, b and c
+fn cross(g: &MetricComponents, a: [f64; 4], b: [f64; 4], c: [f64; 4]) -> [f64; 4] {
+    let (a, b, c) = (lower(g, a), lower(g, b), lower(g, c));
+    let volume = (-determinant(g)).max(crate::DIV_EPSILON).sqrt();
+    std::array::from_fn(|m| {
+        let mut total = 0.;
+        for (n, a) in a.iter().enumerate() {
+            for (r, b) in b.iter().enumerate() {
+                for (s, c) in c.iter().enumerate() {
+                    total += permutation_sign([m, n, r, s]) * a * b * c;
+                }
+            }
+        }
+        -total / volume
+    })
+}
+
+// Unit polarisation vector of light emitted along the ray by gas orbiting at the given angular
+// velocity, and sin² of the angle between the ray and the field in the frame of the gas.
+// Synchrotron light is polarised along k × B there, i.e. f = ε(u, k, B).
+pub(crate) fn emitted_polarisation<S: GeodesicState, M: Metric<S>>(
+    metric: &M,
+    state: S,
+    angular_velocity: f64,
+    field: MagneticField,
+) -> Option<([f64; 4], f64)> {
+    let g = metric.components(state.coordinates());
+    let k = state.velocity();
+
+    // u = u^t (∂t + Ω ∂φ)
+    let gas = combine(
+        [1., 0., 0., 0.],
+        state.rotation_generator(),
+        angular_velocity,
+    );
+    let norm = -dot(&g, gas, gas);
+    if norm <= crate::DIV_EPSILON {
+        return None;
+    }
+    let u = scale(gas, 1. / norm.sqrt());
+
+    // Field and ray directions in the frame of the gas
+    let field = coordinate_vector(&state, field.direction(state.cartesian_position()));
+    let field = combine(field, u, dot(&g, field, u));
+    let field_norm = dot(&g, field, field);
+    if field_norm <= crate::DIV_EPSILON {
+        return None;
+    }
+    let field = scale(field, 1. / field_norm.sqrt());
+
+    let energy = -dot(&g, k, u);
+    let direction = scale(combine(k, u, -energy), 1. / energy);
+    let sin2 = (1. - dot(&g, direction, field).powi(2)).clamp(0., 1.);
+
+    let polarisation = cross(&g, u, k, field);
+    let norm = dot(&g, polarisation, polarisation);
+    if norm <= crate::DIV_EPSILON {
+        return None;
+    }
+    Some((scale(polarisation, 1. / norm.sqrt()), sin2))
+}
+
+// Screen directions of a ray at the camera, right then up, as unit vectors orthogonal to the
+// observer u and to the ray. Up is the projection of the z axis, as seen along the ray direction.
+pub(crate) fn screen_basis<S: GeodesicState, M: Metric<S>>(
+    metric: &M,
+    state: S,
+    u: [f64; 4],
+    direction: CartesianCoords3D,
+) -> [[f64; 4]; 2] {
+    let g = metric.components(state.coordinates());
+    let k = state.velocity();
+
+    let energy = -dot(&g, k, u);
+    let ray = scale(combine(k, u, -energy), 1. / energy);
+
+    let world_up = CartesianCoords3D::cartesian(0., 0., 1.);
+    let mut right = direction.cross(world_up);
+    if right.norm() < crate::DIV_EPSILON {
+        right = CartesianCoords3D::cartesian(1., 0., 0.);
+    }
+    let up = right.cross(direction);
+
+    let right = orthonormalize(&g, coordinate_vector(&state, right), u, &[ray]);
+    let up = orthonormalize(&g, coordinate_vector(&state, up), u, &[ray, right]);
+    [right, up]
+}
+
+// Angle on the screen, from up towards right, of the polarisation with the given Walker-Penrose
+// constant. It is linear in the polarisation, so the screen components solve a 2×2 system.
+pub(crate) fn screen_angle<S: GeodesicState, M: Metric<S>>(
+    metric: &M,
+    state: S,
+    basis: &[[f64; 4]; 2],
+    (kappa1, kappa2): (f64, f64),
+) -> Option<f64> {
+    let [right, up] = basis;
+    let (right1, right2) = metric.walker_penrose(state.coordinates(), state.velocity(), *right)?;
+    let (up1, up2) = metric.walker_penrose(state.coordinates(), state.velocity(), *up)?;
+
+    let determinant = right1 * up2 - up1 * right2;
+    if determinant.abs() < crate::DIV_EPSILON {
+        return None;
+    }
+    let along_right = (kappa1 * up2 - up1 * kappa2) / determinant;
+    let along_up = (right1 * kappa2 - kappa1 * right2) / determinant;
+    Some(along_right.atan2(along_up))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CartesianState3D, Kerr, SphericalState4D};
+
+    // Parallel transport df/dλ = -Γ(k, f). The geodesic acceleration a(v) = -Γ(v, v) is
+    // quadratic in v, so Γ(k, f) = (a(k - f) - a(k + f)) / 4.
+    fn transport(metric: &Kerr, state: SphericalState4D, f: [f64; 4]) -> [f64; 4] {
+        let k = state.velocity();
+        let acceleration = |v: [f64; 4]| metric.geodesic(state.with_velocity(v)).velocity();
+        scale(
+            combine(
+                acceleration(combine(k, f, 1.)),
+                acceleration(combine(k, f, -1.)),
+                -1.,
+            ),
+            0.25,
+        )
+    }
+
+    #[test]
+    fn walker_penrose_constant_is_conserved_along_a_kerr_ray() {
+        let metric = Kerr::new(2., 0.9);
+        let mut state: SphericalState4D =
+            metric.null_state(CartesianState3D::cartesian(20., 0., 5., -1., -0.35, 0.05));
+
+        // Along θ, made orthogonal to the ray with a time component
+        let g = metric.components(state.coordinates());
+        let k = state.velocity();
+        let (theta, time) = ([0., 0., 1., 0.], [1., 0., 0., 0.]);
+        let mut f = combine(theta, time, -dot(&g, theta, k) / dot(&g, time, k));
+
+        let kappa = |state: SphericalState4D, f| {
+            metric
+                .walker_penrose(state.coordinates(), state.velocity(), f)
+                .unwrap()
+        };
+        let (initial1, initial2) = kappa(state, f);
+        let scale_of_kappa = initial1.hypot(initial2);
+
+        // Fourth-order Runge-Kutta on the ray and its polarisation together, past the black hole
+        let mut closest: f64 = state.r();
+        for _ in 0..20_000 {
+            let h = 0.01 * state.r();
+            let derivative = |state: SphericalState4D, f: [f64; 4]| {
+                (metric.geodesic(state), transport(&metric, state, f))
+            };
+            let (s1, f1) = derivative(state, f);
+            let (s2, f2) = derivative(state + s1 * (0.5 * h), combine(f, f1, 0.5 * h));
+            let (s3, f3) = derivative(state + s2 * (0.5 * h), combine(f, f2, 0.5 * h));
+            let (s4, f4) = derivative(state + s3 * h, combine(f, f3, h));
+            state = state + (s1 + s2 * 2. + s3 * 2. + s4) * (h / 6.);
+            f = std::array::from_fn(|i| f[i] + h / 6. * (f1[i] + 2. * f2[i] + 2. * f3[i] + f4[i]));
+            closest = closest.min(state.r());
+            if state.r() > 30. {
+                break;
+            }
+        }
+        assert!(closest < 8., "the ray stayed at r > {closest}");
+        assert!(state.r() > 30., "the ray didn't get away");
+
+        let (kappa1, kappa2) = kappa(state, f);
+        for (actual, expected) in [(kappa1, initial1), (kappa2, initial2)] {
+            assert!(
+                (actual - expected).abs() < 1e-6 * scale_of_kappa,
+                "κ went from ({initial1}, {initial2}) to ({kappa1}, {kappa2})"
+            );
+        }
+    }
+}
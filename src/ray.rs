@@ -1,15 +1,78 @@
-use crate::{BlackHole, Skybox};
+use crate::aov::{HitKind, RayRecord};
+use crate::black_hole::{DiskCrossing, DiskSample};
+use crate::debug_trace::{StepTry, TraceStep};
+use crate::observer;
+use crate::polarisation;
+use crate::spectrum::Spectrum;
+use crate::{BlackHole, GeodesicState, Metric, Norm, Observer, Skybox, Star, StarHit, Stokes};
 use crate::{CartesianCoords3D, CartesianState3D, SphericalState4D};
 use macroquad::prelude::*;
 use std::sync::Arc;
 
+// Conserved momentum of a photon reaching the camera: the energy -p_t and the angular momentum p_φ
+// around the z axis, both divided by the energy measured by the camera.
+// Rays are traced with k = -p, which leaves these ratios unchanged.
+#[derive(Debug, Clone, Copy)]
+pub struct Photon {
+    energy: f64,
+    angular_momentum: f64,
+}
+
+impl Photon {
+    pub fn new(energy: f64, angular_momentum: f64) -> Self {
+        Self {
+            energy,
+            angular_momentum,
+        }
+    }
+
+    pub fn from_state<S: GeodesicState, M: Metric<S>>(
+        metric: &M,
+        state: S,
+        camera_energy: f64,
+    ) -> Self {
+        let momentum = metric.covariant_velocity(state);
+        let angular_momentum: f64 = momentum
+            .iter()
+            .zip(state.rotation_generator())
+            .map(|(k, xi)| k * xi)
+            .sum();
+        Self::new(
+            -momentum[0] / camera_energy,
+            angular_momentum / camera_energy,
+        )
+    }
+
+    pub fn energy(&self) -> f64 {
+        self.energy
+    }
+
+    pub fn angular_momentum(&self) -> f64 {
+        self.angular_momentum
+    }
+
+    // g = E_observed / E_emitted for light emitted by the disk gas at the given radius,
+    // i.e. (p.u_camera) / (p.u_gas) with u_gas = u^t (∂t + Ω ∂φ)
+    pub fn redshift(&self, black_hole: &BlackHole, radius: f64) -> f64 {
+        let (omega, ut) = black_hole.keplerian_orbit(radius);
+        1. / (ut * (self.energy - omega * self.angular_momentum)).max(crate::DIV_EPSILON)
+    }
+}
+
+// Disk hits don't stop the ray, which moves past them and may go through the disk again
 pub enum StoppingCriterion {
     EnteredEventHorizon,
     OutOfBoundingBox(CartesianCoords3D),
-    CrossedAccretionDisk(f64),
+    CrossedAccretionDisk(DiskCrossing, Photon),
+    // Went through a thick disk during the last step
+    TraversedDiskVolume(Vec<DiskSample>, Photon),
+    // Stars are opaque
+    HitStar(StarHit),
+    // The step failed away from the black hole, the ray is left with the background colour
+    IntegrationFailed,
 }
 
-fn determine_color(
+pub(crate) fn determine_color(
     stopping_criterion: &StoppingCriterion,
     black_hole: BlackHole,
     skybox: &Arc<Skybox>,
@@ -17,14 +80,40 @@ fn determine_color(
     match stopping_criterion {
         StoppingCriterion::EnteredEventHorizon => black_hole.color(),
         StoppingCriterion::OutOfBoundingBox(direction) => skybox.sample(direction),
-        StoppingCriterion::CrossedAccretionDisk(r) => black_hole
+        StoppingCriterion::CrossedAccretionDisk(crossing, photon) => black_hole
             .accretion_disk()
-            .get_color(*r)
+            .get_color(crossing, photon.redshift(&black_hole, crossing.radius()))
             .unwrap_or(Color::from_rgba(0, 0, 0, 0)),
+        StoppingCriterion::TraversedDiskVolume(samples, photon) => {
+            let disk = black_hole.accretion_disk();
+            let (color, transmittance) = samples
+                .iter()
+                .filter_map(|sample| {
+                    disk.get_volume_color(sample, photon.redshift(&black_hole, sample.radius()))
+                })
+                .fold(
+                    (Color::new(0., 0., 0., 0.), 1.0),
+                    |(color, transmittance), sample| blend(color, sample, transmittance),
+                );
+            // blend weights the color by its opacity again
+            let opacity = (1.0 - transmittance).max(f32::EPSILON);
+            Color::new(
+                color.r / opacity,
+                color.g / opacity,
+                color.b / opacity,
+                1.0 - transmittance,
+            )
+        }
+        StoppingCriterion::HitStar(hit) => hit.get_color(),
+        StoppingCriterion::IntegrationFailed => crate::BACKGROUND_COLOR,
     }
 }
 
-fn blend(accumulated_color: Color, sample_color: Color, transmittance: f32) -> (Color, f32) {
+pub(crate) fn blend(
+    accumulated_color: Color,
+    sample_color: Color,
+    transmittance: f32,
+) -> (Color, f32) {
     let new_transmittance = transmittance * (1.0 - sample_color.a);
 
     let r = accumulated_color.r + sample_color.r * sample_color.a * transmittance;
@@ -37,7 +126,23 @@ fn blend(accumulated_color: Color, sample_color: Color, transmittance: f32) -> (
     )
 }
 
-fn gamma_correct(linear_color: Color) -> Color {
+fn color_to_spectrum(color: Color) -> (Spectrum, f32) {
+    let spectrum = Spectrum::from_linear_srgb(color.r as f64, color.g as f64, color.b as f64);
+    (spectrum, color.a)
+}
+
+pub(crate) fn blend_spectrum(
+    accumulated_spectrum: Spectrum,
+    sample_spectrum: Spectrum,
+    opacity: f32,
+    transmittance: f32,
+) -> (Spectrum, f32) {
+    let new_transmittance = transmittance * (1.0 - opacity);
+    let spectrum = accumulated_spectrum + sample_spectrum * (opacity * transmittance) as f64;
+    (spectrum, new_transmittance)
+}
+
+pub(crate) fn gamma_correct(linear_color: Color) -> Color {
     const INVERSE_GAMMA: f32 = 1.0 / 2.2;
     Color {
         r: linear_color.r.powf(INVERSE_GAMMA),
@@ -47,70 +152,427 @@ fn gamma_correct(linear_color: Color) -> Color {
     }
 }
 
-pub struct Ray {
-    state: SphericalState4D,
+fn luminance(linear_color: Color) -> f64 {
+    (0.2126 * linear_color.r + 0.7152 * linear_color.g + 0.0722 * linear_color.b) as f64
+}
+
+// Luminance of a colour returned by get_color, back in linear units
+pub(crate) fn linear_luminance(color: Color) -> f64 {
+    const GAMMA: f32 = 2.2;
+    luminance(Color::new(
+        color.r.powf(GAMMA),
+        color.g.powf(GAMMA),
+        color.b.powf(GAMMA),
+        color.a,
+    ))
+}
+
+pub struct Ray<M: Metric<S>, S: GeodesicState = SphericalState4D> {
+    state: S,
     dλ: f64,
+    metric: M,
+    // -k.u of the traced ray at the camera, photon momenta are measured relative to it
+    camera_energy: f64,
+    outside_horizon: bool,
+    // Number of times the ray went through the equatorial plane
+    crossings: usize,
+    // Where the ray left the camera, polarisation is measured there
+    camera_state: S,
+    camera_direction: CartesianCoords3D,
+    observer_velocity: [f64; 4],
+    stars: Arc<[Star]>,
+    // Star behind a disk crossing of the last step, it stops the ray at the next one
+    star_behind_disk: Option<StarHit>,
+    // Integration statistics, see RayRecord
+    steps: usize,
+    retries: usize,
+    min_radius: f64,
+    affine_length: f64,
+    // Every step when debugging the ray, see RayTrace
+    step_log: Option<Vec<TraceStep<S>>>,
+    // Where the last step started and its length, to find the states inside it
+    step_start: S,
+    step_length: f64,
 }
 
-impl Ray {
-    pub fn new(spatial_state: CartesianState3D, rs: f64, dλ0: f64) -> Self {
+impl<M: Metric<S>, S: GeodesicState> Ray<M, S> {
+    pub fn new(spatial_state: CartesianState3D, metric: M, dλ0: f64) -> Self {
+        let state = metric.null_state(spatial_state);
+        let observer = metric.observer_velocity(state.coordinates());
+        Self::from_camera(state, observer, spatial_state, metric, dλ0)
+    }
+
+    // Ray leaving a camera moving as the given observer. The direction of spatial_state is
+    // measured in the tetrad of the observer, which aberrates it, and photon energies are
+    // measured by the observer, which Doppler-shifts the disk.
+    pub fn with_observer(
+        spatial_state: CartesianState3D,
+        observer: Observer,
+        metric: M,
+        dλ0: f64,
+    ) -> Self {
+        let state = S::from_spatial(spatial_state);
+        let g = metric.components(state.coordinates());
+        let u = observer.velocity(&metric, state);
+        let [_, x, y, z] = observer::tetrad(&g, state, u);
+
+        // Traced backwards, k = -p = n - u with n the viewing direction
+        let direction = CartesianCoords3D::cartesian(
+            spatial_state.dx(),
+            spatial_state.dy(),
+            spatial_state.dz(),
+        )
+        .normalize();
+        let n = observer::combine(
+            observer::combine(observer::scale(x, direction.x()), y, direction.y()),
+            z,
+            direction.z(),
+        );
+        let state = state.with_velocity(observer::combine(n, u, -1.));
+        Self::from_camera(state, u, spatial_state, metric, dλ0)
+    }
+
+    fn from_camera(
+        state: S,
+        observer_velocity: [f64; 4],
+        spatial_state: CartesianState3D,
+        metric: M,
+        dλ0: f64,
+    ) -> Self {
+        let camera_energy: f64 = -metric
+            .covariant_velocity(state)
+            .iter()
+            .zip(observer_velocity)
+            .map(|(k, u)| k * u)
+            .sum::<f64>();
         Self {
-            state: spatial_state.to_spherical().to_4d(rs),
+            state,
             dλ: dλ0,
+            metric,
+            camera_energy,
+            outside_horizon: false,
+            crossings: 0,
+            camera_state: state,
+            camera_direction: CartesianCoords3D::cartesian(
+                spatial_state.dx(),
+                spatial_state.dy(),
+                spatial_state.dz(),
+            ),
+            observer_velocity,
+            stars: Arc::from([]),
+            star_behind_disk: None,
+            steps: 0,
+            retries: 0,
+            min_radius: metric.radius(state),
+            affine_length: 0.,
+            step_log: None,
+            step_start: state,
+            step_length: 0.,
+        }
+    }
+
+    // Stars the ray can hit, where they were when light left them
+    pub fn with_stars(self, stars: Arc<[Star]>) -> Self {
+        Self { stars, ..self }
+    }
+
+    // Logs every step from now on, see step_log
+    pub fn with_step_log(self) -> Self {
+        Self {
+            step_log: Some(Vec::new()),
+            ..self
         }
     }
 
+    pub fn state(&self) -> S {
+        self.state
+    }
+
+    // Steps taken since with_step_log, empty without it
+    pub fn step_log(&self) -> &[TraceStep<S>] {
+        self.step_log.as_deref().unwrap_or_default()
+    }
+
+    // Boyer-Lindquist time light takes from the state to the camera. Rays are traced
+    // backwards, so t decreases along them.
+    fn delay(&self, state: S) -> f64 {
+        self.metric.coordinate_time(self.camera_state) - self.metric.coordinate_time(state)
+    }
+
+    fn is_captured(&mut self, black_hole: BlackHole) -> bool {
+        let r = self.metric.radius(self.state);
+        if r > black_hole.visual_radius() {
+            self.outside_horizon = true;
+            return false;
+        }
+
+        // A ray starting inside the horizon may still escape to the outside.
+        // Keep integrating until it does, or until it reaches the central singularity.
+        if self.metric.is_horizon_penetrating() && !self.outside_horizon {
+            return r <= black_hole.radius() * crate::SINGULARITY_RADIUS_FACTOR;
+        }
+        true
+    }
+
+    // First star the segment enters, where the fraction of the segment before it is the
+    // smallest. Stars are seen at the time of the disk minus the delays.
+    fn check_stars(
+        &self,
+        black_hole: BlackHole,
+        (position1, position2): (CartesianCoords3D, CartesianCoords3D),
+        (delay1, delay2): (f64, f64),
+    ) -> Option<(f64, StarHit)> {
+        let time = black_hole.accretion_disk().time();
+        self.stars
+            .iter()
+            .filter_map(|star| {
+                let (fraction, velocity) =
+                    star.check_intersection(position1, position2, time - delay1, time - delay2)?;
+                let redshift = self.star_redshift(velocity, fraction, black_hole.radius())?;
+                Some((fraction, StarHit::new(star.temperature(), redshift)))
+            })
+            .min_by(|(a, _), (b, _)| a.total_cmp(b))
+    }
+
+    // g = (k.u_camera) / (k.u_star) for a star moving with the velocity dx/dt, hit the given
+    // fraction of the way through the last step, u_star being proportional to ∂t + v
+    fn star_redshift(&self, velocity: CartesianCoords3D, fraction: f64, rs: f64) -> Option<f64> {
+        let state = self.state_within_step(fraction, rs);
+        let g = self.metric.components(state.coordinates());
+        let star = observer::combine(
+            [1., 0., 0., 0.],
+            observer::coordinate_vector(&state, velocity),
+            1.,
+        );
+        let norm = -observer::dot(&g, star, star);
+        if norm <= crate::DIV_EPSILON {
+            return None;
+        }
+        let energy = -observer::dot(&g, state.velocity(), star) / norm.sqrt();
+        Some(self.camera_energy / energy)
+    }
+
+    // A thick disk is sampled along the straight chord of each step, which may leave the curved
+    // ray far behind when the step is long. Returns a shorter step when the chord of this one
+    // goes through the slab of the disk over more than DISK_VOLUME_MAX_CHORD scale heights.
+    fn shortened_step(&self, black_hole: &BlackHole, state: S, taken: f64) -> Option<f64> {
+        let disk = black_hole.accretion_disk();
+        if !disk.is_thick() || taken <= black_hole.radius() * crate::RKF45_MIN_STEP_FACTOR {
+            return None;
+        }
+        let position1 = self.metric.cartesian_position(self.state);
+        let position2 = self.metric.cartesian_position(state);
+        let (t_start, t_end) = disk.slab_overlap(position1, position2)?;
+        let length = (position2 - position1).norm();
+        let max_chord = crate::DISK_VOLUME_MAX_CHORD * disk.scale_height(disk.r_isco());
+        (length * (t_end - t_start) > max_chord).then(|| {
+            (taken * max_chord / length).max(black_hole.radius() * crate::RKF45_MIN_STEP_FACTOR)
+        })
+    }
+
     pub fn step(
         &mut self,
         black_hole: BlackHole,
         bounding_box_radius: f64,
     ) -> Option<StoppingCriterion> {
-        if self.state.r() <= black_hole.visual_radius() {
+        if let Some(hit) = self.star_behind_disk.take() {
+            return Some(StoppingCriterion::HitStar(hit));
+        }
+        if self.is_captured(black_hole) {
             return Some(StoppingCriterion::EnteredEventHorizon);
         }
 
-        let (state, dλ) =
-            match crate::geodesic::solve_geodesic_rkf45(self.state, black_hole.radius(), self.dλ) {
-                Ok((state, dλ)) => (state, dλ),
-                Err(_) => {
-                    if self.state.r() < black_hole.visual_radius() {
-                        // RKF Step failed because we are very close to Black Hole. We therefore consider that we fell into it.
-                        return Some(StoppingCriterion::EnteredEventHorizon);
-                    } else {
-                        // Should never happen, only a safety precaution :)
-                        return Some(StoppingCriterion::OutOfBoundingBox(
-                            self.state.spatial_position().to_cartesian(),
-                        ));
-                    }
+        let logging = self.step_log.is_some();
+        let (mut tries, mut logged_tries) = (0, Vec::new());
+        let result = crate::geodesic::solve_geodesic_rkf45_with_tries(
+            self.state,
+            &self.metric,
+            black_hole.radius(),
+            self.dλ,
+            |dλ, error| {
+                tries += 1;
+                if logging {
+                    logged_tries.push(StepTry::new(dλ, error));
                 }
+            },
+        );
+        let shortened = match &result {
+            Ok((state, taken, _)) => self.shortened_step(&black_hole, *state, *taken),
+            Err(_) => None,
+        };
+        if let Some(log) = &mut self.step_log {
+            let state = match (&result, shortened) {
+                (Ok((state, _, _)), None) => *state,
+                _ => self.state,
             };
-
-        // Check if we crossed accretion disk
-        if let Some(r) = black_hole.accretion_disk().check_intersection(
-            self.state.spatial_position().to_cartesian(),
-            state.spatial_position().to_cartesian(),
-        ) {
-            return Some(StoppingCriterion::CrossedAccretionDisk(r));
+            let accepted = result.is_ok() && shortened.is_none();
+            log.extend(TraceStep::new(state, logged_tries, accepted));
+        }
+        if let Some(dλ) = shortened {
+            // Take the step again, shorter
+            self.retries += tries;
+            self.dλ = dλ;
+            return None;
+        }
+        let (state, taken, dλ) = match result {
+            Ok((state, taken, dλ)) => {
+                self.steps += 1;
+                self.retries += tries - 1;
+                self.min_radius = self.min_radius.min(self.metric.radius(state));
+                self.affine_length += taken;
+                (state, taken, dλ)
+            }
+            Err(_) => {
+                self.retries += tries;
+                if self.metric.radius(self.state) < black_hole.visual_radius() {
+                    // RKF Step failed because we are very close to Black Hole. We therefore consider that we fell into it.
+                    return Some(StoppingCriterion::EnteredEventHorizon);
+                } else {
+                    // Should never happen, only a safety precaution :)
+                    return Some(StoppingCriterion::IntegrationFailed);
+                }
+            }
         };
 
-        if state.r() > bounding_box_radius
-            && state.spatial_position().dot(self.state.spatial_velocity()) > 0.
-        {
+        if self.metric.radius(state) > bounding_box_radius && state.is_receding() {
             // We are very far from the black hole AND we are moving away from it
             // Then early stopping. We are going to infinity so use background color.
             return Some(StoppingCriterion::OutOfBoundingBox(
-                state.spatial_position().to_cartesian(),
+                self.metric.cartesian_position(state),
             ));
         }
 
         // We haven't converged yet. Keep the state to make a next step
+        let previous_state = self.state;
         self.state = state;
         self.dλ = dλ;
+        self.step_start = previous_state;
+        self.step_length = taken;
+
+        let (position1, position2) = (
+            self.metric.cartesian_position(previous_state),
+            self.metric.cartesian_position(state),
+        );
+        let (delay1, delay2) = (self.delay(previous_state), self.delay(state));
+        let order = self.crossings;
+        if (position1.z() < 0.) != (position2.z() < 0.) {
+            self.crossings += 1;
+        }
+
+        // A star hides the part of the segment behind it
+        let star = self.check_stars(black_hole, (position1, position2), (delay1, delay2));
+        let (position2, delay2) = match star {
+            Some((fraction, _)) => (
+                position1 + (position2 - position1) * fraction,
+                delay1 + fraction * (delay2 - delay1),
+            ),
+            None => (position2, delay2),
+        };
+        let star = star.map(|(_, hit)| hit);
+
+        // Check if we went through the accretion disk
+        let disk = black_hole.accretion_disk();
+        if disk.is_thick() {
+            let samples = disk.sample_volume(position1, position2, delay1, delay2, order);
+            if !samples.is_empty() {
+                let photon = Photon::from_state(&self.metric, state, self.camera_energy);
+                self.star_behind_disk = star;
+                return Some(StoppingCriterion::TraversedDiskVolume(samples, photon));
+            }
+        } else if let Some((position, fraction)) = disk.check_intersection(position1, position2) {
+            let photon = Photon::from_state(&self.metric, state, self.camera_energy);
+            let delay = delay1 + fraction * (delay2 - delay1);
+            let cos_incidence = (position2 - position1).normalize().z().abs();
+            let crossing = DiskCrossing::new(position, delay, cos_incidence, order);
+            self.star_behind_disk = star;
+            return Some(StoppingCriterion::CrossedAccretionDisk(crossing, photon));
+        }
+
+        if let Some(hit) = star {
+            return Some(StoppingCriterion::HitStar(hit));
+        }
 
         // Signal that we haven't converged by not giving any color
         None
     }
 
+    // State of the ray the given fraction of the way through the last step, integrated again
+    // from where the step started
+    fn state_within_step(&self, fraction: f64, rs: f64) -> S {
+        let mut state = self.step_start;
+        let mut remaining = fraction.clamp(0., 1.) * self.step_length;
+        while remaining > rs * crate::RKF45_MIN_STEP_FACTOR {
+            match crate::geodesic::solve_geodesic_rkf45_with_step(
+                state,
+                &self.metric,
+                rs,
+                remaining,
+            ) {
+                Ok((next, taken, _)) => {
+                    state = next;
+                    remaining -= taken;
+                }
+                Err(_) => break,
+            }
+        }
+        state
+    }
+
+    // Fraction of the way through the last step of the point of its chord closest to the
+    // position
+    fn fraction_within_step(&self, position: CartesianCoords3D) -> f64 {
+        let start = self.metric.cartesian_position(self.step_start);
+        let chord = self.metric.cartesian_position(self.state) - start;
+        let length2 = chord.dot(chord);
+        if length2 <= crate::DIV_EPSILON {
+            return 1.;
+        }
+        ((position - start).dot(chord) / length2).clamp(0., 1.)
+    }
+
+    // Angle on the screen and polarised fraction of the light emitted by the disk gas at the
+    // given position of the last step, see get_stokes
+    fn disk_polarisation(
+        &self,
+        black_hole: BlackHole,
+        screen: &[[f64; 4]; 2],
+        position: CartesianCoords3D,
+    ) -> Option<(f64, f64)> {
+        let disk = black_hole.accretion_disk();
+        let fraction = self.fraction_within_step(position);
+        let state = self.state_within_step(fraction, black_hole.radius());
+        let angular_velocity = disk.angular_velocity(position.x().hypot(position.y()));
+        let (polarisation, sin2) = polarisation::emitted_polarisation(
+            &self.metric,
+            state,
+            angular_velocity,
+            disk.magnetic_field(),
+        )?;
+        let kappa =
+            self.metric
+                .walker_penrose(state.coordinates(), state.velocity(), polarisation)?;
+        let angle = polarisation::screen_angle(&self.metric, self.camera_state, screen, kappa)?;
+        Some((angle, crate::MAX_POLARISATION_FRACTION * sin2))
+    }
+
+    // Integrates the ray and hands every stopping point to on_hit, which returns the transmittance left
+    fn trace<F>(&mut self, black_hole: BlackHole, bounding_box_radius: f64, mut on_hit: F)
+    where
+        F: FnMut(&Self, StoppingCriterion) -> f32,
+    {
+        for i in 0..crate::NUM_INTEGRATION_STEPS {
+            if i > 0 && i % crate::NORMALIZATION_INTERVAL == 0 {
+                self.state = self.metric.renormalize(self.state);
+            }
+
+            if let Some(criterion) = self.step(black_hole, bounding_box_radius)
+                && on_hit(self, criterion) < 0.05
+            {
+                break;
+            }
+        }
+    }
+
     pub fn get_color(
         &mut self,
         black_hole: BlackHole,
@@ -120,23 +582,336 @@ impl Ray {
         let mut accumulated_color = Color::new(0.0, 0.0, 0.0, 0.0);
         let mut transmittance = 1.0;
 
-        for i in 0..crate::NUM_INTEGRATION_STEPS {
-            if i > 0 && i % crate::NORMALIZATION_INTERVAL == 0 {
-                self.state = self.state.renormalize(black_hole.radius());
+        self.trace(black_hole, bounding_box_radius, |_, criterion| {
+            let hit_color = determine_color(&criterion, black_hole, &skybox);
+            (accumulated_color, transmittance) = blend(accumulated_color, hit_color, transmittance);
+            transmittance
+        });
+
+        let (final_color, _) = blend(accumulated_color, crate::BACKGROUND_COLOR, transmittance);
+
+        gamma_correct(final_color)
+    }
+
+    // Same as get_color, along with what happened to the ray, see RayRecord
+    pub fn get_color_with_record(
+        &mut self,
+        black_hole: BlackHole,
+        bounding_box_radius: f64,
+        skybox: Arc<Skybox>,
+    ) -> (Color, RayRecord) {
+        let mut accumulated_color = Color::new(0.0, 0.0, 0.0, 0.0);
+        let mut transmittance = 1.0;
+        let mut hit = HitKind::Unfinished;
+        let (mut escape_direction, mut disk_radius, mut redshift) = (None, None, None);
+
+        self.trace(black_hole, bounding_box_radius, |ray, criterion| {
+            let (kind, radius, g) = match &criterion {
+                StoppingCriterion::EnteredEventHorizon => (HitKind::Horizon, None, None),
+                StoppingCriterion::OutOfBoundingBox(direction) => {
+                    escape_direction = Some(direction.normalize());
+                    let photon = Photon::from_state(&ray.metric, ray.state, ray.camera_energy);
+                    (HitKind::Escaped, None, Some(1. / photon.energy()))
+                }
+                StoppingCriterion::CrossedAccretionDisk(crossing, photon) => (
+                    HitKind::Disk,
+                    Some(crossing.radius()),
+                    Some(photon.redshift(&black_hole, crossing.radius())),
+                ),
+                StoppingCriterion::TraversedDiskVolume(samples, photon) => {
+                    let radius = samples.first().map(|sample| sample.radius());
+                    let g = radius.map(|radius| photon.redshift(&black_hole, radius));
+                    (HitKind::DiskVolume, radius, g)
+                }
+                StoppingCriterion::HitStar(star) => (HitKind::Star, None, Some(star.redshift())),
+                StoppingCriterion::IntegrationFailed => (HitKind::Unfinished, None, None),
+            };
+            // Everything describes the first hit, see RayRecord
+            if hit == HitKind::Unfinished {
+                (hit, disk_radius, redshift) = (kind, radius, g);
             }
 
-            if let Some(criterion) = self.step(black_hole, bounding_box_radius) {
-                let hit_color = determine_color(&criterion, black_hole, &skybox);
-                (accumulated_color, transmittance) =
-                    blend(accumulated_color, hit_color, transmittance);
-                if transmittance < 0.05 {
-                    break;
+            let hit_color = determine_color(&criterion, black_hole, &skybox);
+            (accumulated_color, transmittance) = blend(accumulated_color, hit_color, transmittance);
+            transmittance
+        });
+
+        let (final_color, _) = blend(accumulated_color, crate::BACKGROUND_COLOR, transmittance);
+        let record = RayRecord {
+            hit,
+            steps: self.steps,
+            retries: self.retries,
+            min_radius: self.min_radius,
+            affine_length: self.affine_length,
+            escape_direction,
+            disk_radius,
+            redshift,
+        };
+        (gamma_correct(final_color), record)
+    }
+
+    // Same as get_color, along with the Stokes parameters of the light reaching the camera.
+    // The disk gas emits light polarised along k × B in its own frame, like synchrotron
+    // radiation, with a polarised fraction growing as sin² of the angle between the ray and
+    // the magnetic field. The polarisation vector is parallel-transported to the camera through
+    // the Walker-Penrose constant, so metrics without one give unpolarised light, as does the sky.
+    pub fn get_stokes(
+        &mut self,
+        black_hole: BlackHole,
+        bounding_box_radius: f64,
+        skybox: Arc<Skybox>,
+    ) -> (Color, Stokes) {
+        let mut accumulated_color = Color::new(0.0, 0.0, 0.0, 0.0);
+        let mut transmittance = 1.0;
+        let mut stokes = Stokes::default();
+        let screen = polarisation::screen_basis(
+            &self.metric,
+            self.camera_state,
+            self.observer_velocity,
+            self.camera_direction.normalize(),
+        );
+
+        self.trace(black_hole, bounding_box_radius, |ray, criterion| {
+            // Each sample of a thick disk is emitted where it lies, with its own polarisation
+            let emitted: Vec<(Color, Option<CartesianCoords3D>)> = match &criterion {
+                StoppingCriterion::CrossedAccretionDisk(crossing, _) => vec![(
+                    determine_color(&criterion, black_hole, &skybox),
+                    Some(crossing.position()),
+                )],
+                StoppingCriterion::TraversedDiskVolume(samples, photon) => {
+                    let disk = black_hole.accretion_disk();
+                    samples
+                        .iter()
+                        .filter_map(|sample| {
+                            let redshift = photon.redshift(&black_hole, sample.radius());
+                            let color = disk.get_volume_color(sample, redshift)?;
+                            let (sin, cos) = sample.azimuth().sin_cos();
+                            let position = CartesianCoords3D::cartesian(
+                                sample.radius() * cos,
+                                sample.radius() * sin,
+                                sample.height(),
+                            );
+                            Some((color, Some(position)))
+                        })
+                        .collect()
                 }
+                _ => vec![(determine_color(&criterion, black_hole, &skybox), None)],
+            };
+
+            for (color, position) in emitted {
+                let previous_luminance = luminance(accumulated_color);
+                (accumulated_color, transmittance) = blend(accumulated_color, color, transmittance);
+                let intensity = luminance(accumulated_color) - previous_luminance;
+
+                let polarisation = position
+                    .and_then(|position| ray.disk_polarisation(black_hole, &screen, position));
+                stokes = stokes
+                    + match polarisation {
+                        Some((angle, fraction)) => Stokes::linear(intensity, fraction, angle),
+                        None => Stokes::unpolarised(intensity),
+                    };
             }
-        }
+            transmittance
+        });
 
+        let previous_luminance = luminance(accumulated_color);
         let (final_color, _) = blend(accumulated_color, crate::BACKGROUND_COLOR, transmittance);
+        stokes = stokes + Stokes::unpolarised(luminance(final_color) - previous_luminance);
 
-        gamma_correct(final_color)
+        (gamma_correct(final_color), stokes)
+    }
+
+    // Contribution of the disk to get_color split by image order: the direct image first, then
+    // light that went once more through the equatorial plane for each layer. Orders past the
+    // last layer are added to it.
+    pub fn get_order_layers(
+        &mut self,
+        black_hole: BlackHole,
+        bounding_box_radius: f64,
+        skybox: Arc<Skybox>,
+        num_orders: usize,
+    ) -> Vec<Color> {
+        self.get_color_with_layers(black_hole, bounding_box_radius, skybox, num_orders)
+            .1
+    }
+
+    // get_color along with the contribution of the disk alone, all image orders together, from
+    // a single trace
+    pub fn get_color_with_disk(
+        &mut self,
+        black_hole: BlackHole,
+        bounding_box_radius: f64,
+        skybox: Arc<Skybox>,
+    ) -> (Color, Color) {
+        let (color, layers) =
+            self.get_color_with_layers(black_hole, bounding_box_radius, skybox, 1);
+        (color, layers[0])
+    }
+
+    // get_color along with get_order_layers
+    fn get_color_with_layers(
+        &mut self,
+        black_hole: BlackHole,
+        bounding_box_radius: f64,
+        skybox: Arc<Skybox>,
+        num_orders: usize,
+    ) -> (Color, Vec<Color>) {
+        let mut accumulated_color = Color::new(0.0, 0.0, 0.0, 0.0);
+        let mut layers = vec![Color::new(0.0, 0.0, 0.0, 1.0); num_orders.max(1)];
+        let mut transmittance = 1.0;
+
+        let mut add_to_layer = |order: usize, color: Color, transmittance: f32| {
+            let layer = &mut layers[order.min(num_orders.max(1) - 1)];
+            let (contribution, new_transmittance) =
+                blend(Color::new(0.0, 0.0, 0.0, 0.0), color, transmittance);
+            layer.r += contribution.r;
+            layer.g += contribution.g;
+            layer.b += contribution.b;
+            new_transmittance
+        };
+
+        self.trace(black_hole, bounding_box_radius, |_, criterion| {
+            let hit_color = determine_color(&criterion, black_hole, &skybox);
+            match &criterion {
+                StoppingCriterion::CrossedAccretionDisk(crossing, _) => {
+                    add_to_layer(crossing.order(), hit_color, transmittance);
+                }
+                StoppingCriterion::TraversedDiskVolume(samples, photon) => {
+                    let disk = black_hole.accretion_disk();
+                    samples.iter().fold(transmittance, |transmittance, sample| {
+                        let redshift = photon.redshift(&black_hole, sample.radius());
+                        match disk.get_volume_color(sample, redshift) {
+                            Some(color) => add_to_layer(sample.order(), color, transmittance),
+                            None => transmittance,
+                        }
+                    });
+                }
+                _ => {}
+            }
+            (accumulated_color, transmittance) = blend(accumulated_color, hit_color, transmittance);
+            transmittance
+        });
+
+        let (final_color, _) = blend(accumulated_color, crate::BACKGROUND_COLOR, transmittance);
+        (
+            gamma_correct(final_color),
+            layers.into_iter().map(gamma_correct).collect(),
+        )
+    }
+
+    // Same as get_color, but carries a sampled spectrum along the ray so that frequency shifts
+    // apply to the skybox as well as to the disk
+    pub fn get_spectral_color(
+        &mut self,
+        black_hole: BlackHole,
+        bounding_box_radius: f64,
+        skybox: Arc<Skybox>,
+    ) -> Color {
+        let mut accumulated_spectrum = Spectrum::zero();
+        let mut transmittance = 1.0;
+
+        self.trace(black_hole, bounding_box_radius, |ray, criterion| {
+            let (spectrum, opacity) = match criterion {
+                StoppingCriterion::EnteredEventHorizon => color_to_spectrum(black_hole.color()),
+                StoppingCriterion::OutOfBoundingBox(direction) => {
+                    // Light from infinity, shifted by g = E_camera / E_infinity
+                    let (spectrum, opacity) = color_to_spectrum(skybox.sample(&direction));
+                    let photon = Photon::from_state(&ray.metric, ray.state, ray.camera_energy);
+                    (spectrum.redshifted(1. / photon.energy()), opacity)
+                }
+                StoppingCriterion::CrossedAccretionDisk(crossing, photon) => black_hole
+                    .accretion_disk()
+                    .get_spectrum(&crossing, photon.redshift(&black_hole, crossing.radius()))
+                    .unwrap_or((Spectrum::zero(), 0.)),
+                StoppingCriterion::TraversedDiskVolume(samples, photon) => {
+                    let disk = black_hole.accretion_disk();
+                    let (spectrum, transmittance) = samples
+                        .iter()
+                        .filter_map(|sample| {
+                            let redshift = photon.redshift(&black_hole, sample.radius());
+                            disk.get_volume_spectrum(sample, redshift)
+                        })
+                        .fold(
+                            (Spectrum::zero(), 1.0),
+                            |(accumulated, transmittance), (spectrum, opacity)| {
+                                blend_spectrum(accumulated, spectrum, opacity, transmittance)
+                            },
+                        );
+                    let opacity = (1.0 - transmittance).max(f32::EPSILON);
+                    (spectrum * (1. / opacity as f64), 1.0 - transmittance)
+                }
+                StoppingCriterion::HitStar(hit) => (hit.get_spectrum(), 1.0),
+                StoppingCriterion::IntegrationFailed => color_to_spectrum(crate::BACKGROUND_COLOR),
+            };
+            (accumulated_spectrum, transmittance) =
+                blend_spectrum(accumulated_spectrum, spectrum, opacity, transmittance);
+            transmittance
+        });
+
+        let (background, opacity) = color_to_spectrum(crate::BACKGROUND_COLOR);
+        let (final_spectrum, transmittance) =
+            blend_spectrum(accumulated_spectrum, background, opacity, transmittance);
+
+        let (r, g, b) = final_spectrum.to_linear_srgb();
+        gamma_correct(Color::new(
+            r as f32,
+            g as f32,
+            b as f32,
+            1.0 - transmittance,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CartesianCoords4D, Schwarzschild};
+    use image::{DynamicImage, Rgb32FImage};
+
+    #[test]
+    fn disk_seen_through_the_plane_is_in_the_first_order_layer() {
+        let black_hole = BlackHole::new(CartesianCoords4D::cartesian(0., 0., 0., 0.), 1.);
+        let rs = black_hole.radius();
+        let metric = Schwarzschild::from(black_hole);
+        let skybox = Arc::new(Skybox::from_image(DynamicImage::ImageRgb32F(
+            Rgb32FImage::new(2, 2),
+        )));
+
+        // Nearly face-on camera at r = 50 M, tilted by 0.1 rad, and rays in its plane of symmetry
+        let (sin_tilt, cos_tilt) = 0.1f64.sin_cos();
+        let e1 = CartesianCoords3D::cartesian(0., -sin_tilt, cos_tilt);
+        let e2 = CartesianCoords3D::cartesian(0., -cos_tilt, -sin_tilt);
+        let camera = e1 * 50.;
+
+        // From the Binet equation: b = 15 M goes through the plane at r = 13.9 M, and b = 5.7 M
+        // at r = 4.8 M, inside the ISCO, then at r = 11.4 M behind the black hole
+        for (impact_parameter, order) in [(15f64, 0), (5.7, 1)] {
+            let u = black_hole.mass() / 50.;
+            let cot = (impact_parameter.powi(-2) - u.powi(2) * (1. - 2. * u)).sqrt() / u;
+            let direction = e1 * -cot + e2;
+            let spatial_state = CartesianState3D::cartesian(
+                camera.x(),
+                camera.y(),
+                camera.z(),
+                direction.x(),
+                direction.y(),
+                direction.z(),
+            );
+            let layers = Ray::new(spatial_state, metric, rs * crate::INTEGRATION_STEP_FACTOR)
+                .get_order_layers(
+                    black_hole,
+                    rs * crate::BOUNDING_BOX_FACTOR,
+                    Arc::clone(&skybox),
+                    3,
+                );
+            for (i, layer) in layers.iter().enumerate() {
+                let brightness = layer.r + layer.g + layer.b;
+                if i == order {
+                    assert!(brightness > 0.1, "b = {impact_parameter}: empty layer {i}");
+                } else {
+                    assert_eq!(brightness, 0., "b = {impact_parameter}: disk in layer {i}");
+                }
+            }
+        }
     }
 }
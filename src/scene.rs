@@ -1,12 +1,28 @@
 use macroquad::prelude::*;
 use std::f64::consts::PI;
+use std::fs;
+use std::io;
+use std::path::Path;
 use std::sync::Arc;
 
 use crate::BlackHole;
+use crate::DeflectionTable;
+use crate::FreeFallCamera;
+use crate::GeodesicState;
+use crate::Metric;
 use crate::Norm;
+use crate::Observer;
+use crate::PlanarRay;
 use crate::Ray;
+use crate::RayTrace;
 use crate::Skybox;
 use crate::SphericalCoords3D;
+use crate::SphericalState4D;
+use crate::Star;
+use crate::Stokes;
+use crate::aov::{AovBuffers, AovMode, RayRecord};
+use crate::observer;
+use crate::ray::linear_luminance;
 use crate::{CartesianCoords2D, CartesianCoords3D, CartesianCoords4D, CartesianState3D};
 
 fn get_basis(
@@ -20,31 +36,169 @@ fn get_basis(
     (forward, right, up)
 }
 
-fn get_pixel_color(
+fn camera_ray(camera: Camera, ray_direction: CartesianCoords3D) -> CartesianState3D {
+    let camera_coords = camera.position();
+    let ray_direction = camera.to_world_coordinates(ray_direction);
+    CartesianState3D::cartesian(
+        camera_coords.x(),
+        camera_coords.y(),
+        camera_coords.z(),
+        ray_direction.x(),
+        ray_direction.y(),
+        ray_direction.z(),
+    )
+}
+
+fn new_ray<S: GeodesicState, M: Metric<S>>(
+    camera: Camera,
+    ray_direction: CartesianCoords3D,
+    metric: M,
+    dλ0: f64,
+    stars: Arc<[Star]>,
+) -> Ray<M, S> {
+    Ray::with_observer(
+        camera_ray(camera, ray_direction),
+        camera.observer(),
+        metric,
+        dλ0,
+    )
+    .with_stars(stars)
+}
+
+fn get_pixel_color<S: GeodesicState, M: Metric<S>>(
     camera: Camera,
     ray_direction: CartesianCoords3D,
     black_hole: BlackHole,
+    metric: M,
     dλ0: f64,
     skybox: Arc<Skybox>,
+    stars: Arc<[Star]>,
 ) -> Color {
-    let camera_coords = camera.position();
-    let ray_direction = camera.to_world_coordinates(ray_direction);
-    let mut ray = Ray::new(
-        CartesianState3D::cartesian(
-            camera_coords.x(),
-            camera_coords.y(),
-            camera_coords.z(),
-            ray_direction.x(),
-            ray_direction.y(),
-            ray_direction.z(),
-        ),
-        black_hole.radius(),
-        dλ0,
-    );
+    let mut ray = new_ray(camera, ray_direction, metric, dλ0, stars);
+    let bounding_box_radius = black_hole.radius() * crate::BOUNDING_BOX_FACTOR;
+    ray.get_color(black_hole, bounding_box_radius, skybox)
+}
+
+fn get_pixel_color_spectral<S: GeodesicState, M: Metric<S>>(
+    camera: Camera,
+    ray_direction: CartesianCoords3D,
+    black_hole: BlackHole,
+    metric: M,
+    dλ0: f64,
+    skybox: Arc<Skybox>,
+    stars: Arc<[Star]>,
+) -> Color {
+    let mut ray = new_ray(camera, ray_direction, metric, dλ0, stars);
+    let bounding_box_radius = black_hole.radius() * crate::BOUNDING_BOX_FACTOR;
+    ray.get_spectral_color(black_hole, bounding_box_radius, skybox)
+}
+
+fn get_pixel_stokes<S: GeodesicState, M: Metric<S>>(
+    camera: Camera,
+    ray_direction: CartesianCoords3D,
+    black_hole: BlackHole,
+    metric: M,
+    dλ0: f64,
+    skybox: Arc<Skybox>,
+    stars: Arc<[Star]>,
+) -> (Color, Stokes) {
+    let mut ray = new_ray(camera, ray_direction, metric, dλ0, stars);
+    let bounding_box_radius = black_hole.radius() * crate::BOUNDING_BOX_FACTOR;
+    ray.get_stokes(black_hole, bounding_box_radius, skybox)
+}
+
+fn get_pixel_record<S: GeodesicState, M: Metric<S>>(
+    camera: Camera,
+    ray_direction: CartesianCoords3D,
+    black_hole: BlackHole,
+    metric: M,
+    dλ0: f64,
+    skybox: Arc<Skybox>,
+    stars: Arc<[Star]>,
+) -> (Color, RayRecord) {
+    let mut ray = new_ray(camera, ray_direction, metric, dλ0, stars);
+    let bounding_box_radius = black_hole.radius() * crate::BOUNDING_BOX_FACTOR;
+    ray.get_color_with_record(black_hole, bounding_box_radius, skybox)
+}
+
+fn get_pixel_color_orbital_plane(
+    camera: Camera,
+    ray_direction: CartesianCoords3D,
+    black_hole: BlackHole,
+    skybox: Arc<Skybox>,
+) -> Color {
+    let mut ray = PlanarRay::new(camera_ray(camera, ray_direction), black_hole.radius());
     let bounding_box_radius = black_hole.radius() * crate::BOUNDING_BOX_FACTOR;
     ray.get_color(black_hole, bounding_box_radius, skybox)
 }
 
+// Inverse of new_ray: world direction of the pixel whose ray leaves the camera with the velocity
+// of the state, None when it isn't a past-directed null ray
+fn pixel_direction<M: Metric>(
+    camera: Camera,
+    state: SphericalState4D,
+    metric: M,
+) -> Option<CartesianCoords3D> {
+    // k = E (n - u) with n the viewing direction in the tetrad of the observer
+    let g = metric.components(state.coordinates());
+    let u = camera.observer().velocity(&metric, state);
+    let [_, x, y, z] = observer::tetrad(&g, state, u);
+    let k = state.velocity();
+    let energy = observer::dot(&g, k, u);
+    if energy <= crate::DIV_EPSILON {
+        return None;
+    }
+    let n = observer::combine(u, k, 1. / energy);
+    Some(CartesianCoords3D::cartesian(
+        observer::dot(&g, n, x),
+        observer::dot(&g, n, y),
+        observer::dot(&g, n, z),
+    ))
+}
+
+pub(crate) fn image_from_colors(colors: &[Color], width: u16, height: u16) -> Image {
+    let mut image = Image::gen_image_color(width, height, BLACK);
+    for (i, &color) in colors.iter().enumerate() {
+        image.set_pixel(
+            (i % width as usize) as u32,
+            (i / width as usize) as u32,
+            color,
+        );
+    }
+    image
+}
+
+// Grey levels for a Stokes parameter in [0, scale], or red for positive and blue for negative
+// values of a signed one, gamma-corrected like the colours
+fn stokes_image(values: &[f64], scale: f64, signed: bool, width: u16, height: u16) -> Image {
+    let colors: Vec<Color> = values
+        .iter()
+        .map(|&value| {
+            let level = (value / scale.max(f64::MIN_POSITIVE)).clamp(-1., 1.) as f32;
+            let linear = if !signed {
+                Color::new(level, level, level, 1.)
+            } else if level >= 0. {
+                Color::new(level, 0., 0., 1.)
+            } else {
+                Color::new(0., 0., -level, 1.)
+            };
+            crate::ray::gamma_correct(linear)
+        })
+        .collect();
+    image_from_colors(&colors, width, height)
+}
+
+fn save_png(image: &Image, path: &Path) -> io::Result<()> {
+    image::save_buffer(
+        path,
+        &image.bytes,
+        image.width as u32,
+        image.height as u32,
+        image::ColorType::Rgba8,
+    )
+    .map_err(io::Error::other)
+}
+
 #[derive(Debug, Copy, Clone)]
 pub struct Camera {
     position: CartesianCoords3D,
@@ -53,6 +207,7 @@ pub struct Camera {
     up: CartesianCoords3D,
     right: CartesianCoords3D,
     fov: f64,
+    observer: Observer, // motion of the camera, see Ray::with_observer
 }
 
 impl Camera {
@@ -71,6 +226,9 @@ impl Camera {
     pub fn scale(&self) -> f64 {
         (self.fov / 2.0).tan()
     }
+    pub fn observer(&self) -> Observer {
+        self.observer
+    }
 
     pub fn new(position: CartesianCoords3D, target: CartesianCoords3D) -> Self {
         let (forward, right, up) = get_basis(position, target);
@@ -81,9 +239,14 @@ impl Camera {
             up,
             right,
             fov: f64::to_radians(crate::FOV),
+            observer: Observer::Static,
         }
     }
 
+    pub fn with_observer(self, observer: Observer) -> Self {
+        Self { observer, ..self }
+    }
+
     pub fn rotate(&self, angle_x: f64, angle_y: f64) -> Self {
         let position = self.position.to_spherical();
         let theta = (position.theta() + angle_y).clamp(
@@ -92,7 +255,20 @@ impl Camera {
         );
         let phi = position.phi() + angle_x;
         let new_position = SphericalCoords3D::spherical(position.r(), theta, phi);
-        Self::new(new_position.to_cartesian(), self.target)
+        Self::new(new_position.to_cartesian(), self.target).with_observer(self.observer)
+    }
+
+    // Inverse of to_world_coordinates, scaled to a forward component of 1. None behind the camera.
+    pub fn project(&self, direction: CartesianCoords3D) -> Option<CartesianCoords3D> {
+        let forward = direction.dot(self.forward);
+        if forward <= crate::DIV_EPSILON {
+            return None;
+        }
+        Some(CartesianCoords3D::cartesian(
+            direction.dot(self.right) / forward,
+            direction.dot(self.up) / forward,
+            1.,
+        ))
     }
 
     pub fn to_world_coordinates(&self, direction: CartesianCoords3D) -> CartesianCoords3D {
@@ -103,15 +279,18 @@ impl Camera {
     }
 }
 
-pub struct Scene {
+pub struct Scene<M: Metric> {
     camera: Camera,
     scene_size: CartesianCoords2D,
     black_hole: BlackHole,
+    metric: M,
     dλ0: f64,
     skybox: Arc<Skybox>,
+    stars: Arc<[Star]>,
 }
 
-impl Scene {
+impl<M: Metric + From<BlackHole>> Scene<M> {
+    // The metric is built from the black hole, so that both always describe the same spacetime
     pub fn new(scene_width_factor: f64, scene_height_factor: f64, black_hole: BlackHole) -> Self {
         let radius = black_hole.radius();
 
@@ -127,11 +306,15 @@ impl Scene {
             camera,
             scene_size,
             black_hole,
+            metric: M::from(black_hole),
             dλ0: radius * crate::INTEGRATION_STEP_FACTOR,
             skybox,
+            stars: Arc::from([]),
         }
     }
+}
 
+impl<M: Metric> Scene<M> {
     pub fn dλ0(&self) -> f64 {
         self.dλ0
     }
@@ -167,14 +350,49 @@ impl Scene {
         self.black_hole
     }
 
+    pub fn metric(&self) -> M {
+        self.metric
+    }
+
     pub fn skybox(&self) -> Arc<Skybox> {
         Arc::clone(&self.skybox)
     }
 
+    pub fn stars(&self) -> Arc<[Star]> {
+        Arc::clone(&self.stars)
+    }
+
+    // Stars are seen where they were when light left them, relative to the time of the scene
+    pub fn add_star(&mut self, star: Star) {
+        self.stars = self.stars.iter().cloned().chain([star]).collect();
+    }
+
     pub fn camera(&self) -> Camera {
         self.camera
     }
 
+    pub fn set_camera(&mut self, camera: Camera) {
+        self.camera = camera;
+    }
+
+    // The orbital-plane and deflection-table renderers keep a static camera
+    pub fn set_observer(&mut self, observer: Observer) {
+        self.camera = self.camera.with_observer(observer);
+    }
+
+    // Coordinate time of the frame, the disk texture rotates with it
+    pub fn time(&self) -> f64 {
+        self.black_hole.accretion_disk().time()
+    }
+
+    pub fn set_time(&mut self, time: f64) {
+        self.black_hole = self.black_hole.at_time(time);
+    }
+
+    pub fn advance_time(&mut self, dt: f64) {
+        self.set_time(self.time() + dt);
+    }
+
     pub fn rotate_camera(&mut self, angle_x: f64, angle_y: f64) {
         self.camera = self
             .camera
@@ -182,6 +400,403 @@ impl Scene {
     }
 
     pub fn get_image(&self) -> Image {
+        self.render_with_metric(self.metric)
+    }
+
+    // Renders the scene in another coordinate system of the same black hole, e.g. to compare
+    // integration paths
+    pub fn get_image_with_metric<S: GeodesicState, N: Metric<S> + From<BlackHole>>(&self) -> Image {
+        self.render_with_metric(N::from(self.black_hole))
+    }
+
+    fn render_with_metric<S: GeodesicState, N: Metric<S>>(&self, metric: N) -> Image {
+        let dλ0 = self.dλ0();
+        let stars = self.stars();
+        self.render(move |camera, ray_direction, black_hole, skybox| {
+            get_pixel_color(
+                camera,
+                ray_direction,
+                black_hole,
+                metric,
+                dλ0,
+                skybox,
+                Arc::clone(&stars),
+            )
+        })
+    }
+
+    // Colours of get_image row by row, before they are quantised
+    pub fn get_colors(&self) -> Vec<Color> {
+        let metric = self.metric;
+        let dλ0 = self.dλ0();
+        let stars = self.stars();
+        self.render_values(move |camera, ray_direction, black_hole, skybox| {
+            get_pixel_color(
+                camera,
+                ray_direction,
+                black_hole,
+                metric,
+                dλ0,
+                skybox,
+                Arc::clone(&stars),
+            )
+        })
+    }
+
+    // Colours of get_colors along with the contribution of the disk alone, all image orders
+    // together, see Ray::get_color_with_disk
+    fn get_colors_with_disk(&self) -> Vec<(Color, Color)> {
+        let metric = self.metric;
+        let dλ0 = self.dλ0();
+        let stars = self.stars();
+        self.render_values(move |camera, ray_direction, black_hole, skybox| {
+            let mut ray = new_ray(camera, ray_direction, metric, dλ0, Arc::clone(&stars));
+            let bounding_box_radius = black_hole.radius() * crate::BOUNDING_BOX_FACTOR;
+            ray.get_color_with_disk(black_hole, bounding_box_radius, skybox)
+        })
+    }
+
+    // Renders num_frames frames over one orbit of the hot spot of the disk, starting at the
+    // current time. Frames are saved in the directory as PNGs, along with light_curve.csv
+    // giving the flux of the disk and its hot spot in each frame, in arbitrary units, against
+    // the observer time. The skybox, the stars and the shadow are left out of the flux.
+    pub fn render_hot_spot_orbit(&mut self, num_frames: usize, directory: &Path) -> io::Result<()> {
+        let hot_spot = self.black_hole.accretion_disk().hot_spot().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "The accretion disk has no hot spot",
+            )
+        })?;
+        let period = self.black_hole.orbital_period(hot_spot.orbit_radius());
+        let (screen_width, screen_height) = self.screen_size().unpack();
+        let start = self.time();
+
+        fs::create_dir_all(directory)?;
+        let mut light_curve = String::from("frame,time,flux\n");
+
+        for frame in 0..num_frames {
+            let time = start + period * frame as f64 / num_frames as f64;
+            self.set_time(time);
+
+            let (colors, disk): (Vec<Color>, Vec<Color>) =
+                self.get_colors_with_disk().into_iter().unzip();
+            let flux: f64 = disk.iter().map(|&color| linear_luminance(color)).sum();
+            light_curve.push_str(&format!("{frame},{time},{flux}\n"));
+
+            let image = image_from_colors(&colors, screen_width as u16, screen_height as u16);
+            save_png(&image, &directory.join(format!("frame_{frame:04}.png")))?;
+        }
+
+        self.set_time(start);
+        fs::write(directory.join("light_curve.csv"), light_curve)
+    }
+
+    // Puts the camera where the free-falling one is, looking at the black hole and moving with
+    // it, the coordinate time it fell for after start_time
+    pub fn follow(&mut self, camera: &FreeFallCamera<M>, start_time: f64) {
+        self.camera = Camera::new(camera.position(), self.black_hole.coords().position())
+            .with_observer(camera.observer());
+        self.set_time(start_time + camera.coordinate_time());
+    }
+
+    // Renders num_frames frames of the fall of the camera, one every frame_interval of its
+    // proper time, stopping early when it reaches the horizon. Frames are saved in the
+    // directory as PNGs, along with trajectory.csv giving the proper time, coordinate time and
+    // radius of the camera at each frame. Returns the number of frames rendered.
+    pub fn render_free_fall(
+        &mut self,
+        mut camera: FreeFallCamera<M>,
+        frame_interval: f64,
+        num_frames: usize,
+        directory: &Path,
+    ) -> io::Result<usize> {
+        let initial_camera = self.camera;
+        let initial_time = self.time();
+        let (screen_width, screen_height) = self.screen_size().unpack();
+
+        fs::create_dir_all(directory)?;
+        let mut trajectory = String::from("frame,proper_time,coordinate_time,radius\n");
+
+        let mut frame = 0;
+        while frame < num_frames && !camera.has_fallen() {
+            self.follow(&camera, initial_time);
+            trajectory.push_str(&format!(
+                "{frame},{},{},{}\n",
+                camera.proper_time(),
+                camera.coordinate_time(),
+                camera.radius()
+            ));
+
+            let colors = self.get_colors();
+            let image = image_from_colors(&colors, screen_width as u16, screen_height as u16);
+            save_png(&image, &directory.join(format!("frame_{frame:04}.png")))?;
+
+            frame += 1;
+            if camera.advance(frame_interval).is_err() {
+                break;
+            }
+        }
+
+        self.camera = initial_camera;
+        self.set_time(initial_time);
+        fs::write(directory.join("trajectory.csv"), trajectory)?;
+        Ok(frame)
+    }
+
+    // Disk contribution of each image order as a separate image, see Ray::get_order_layers
+    pub fn get_order_layers(&self, num_orders: usize) -> Vec<Image> {
+        let metric = self.metric;
+        let dλ0 = self.dλ0();
+        let stars = self.stars();
+        let layers = self.render_values(move |camera, ray_direction, black_hole, skybox| {
+            let mut ray = new_ray(camera, ray_direction, metric, dλ0, Arc::clone(&stars));
+            let bounding_box_radius = black_hole.radius() * crate::BOUNDING_BOX_FACTOR;
+            ray.get_order_layers(black_hole, bounding_box_radius, skybox, num_orders)
+        });
+
+        let (screen_width, screen_height) = self.screen_size().unpack();
+        (0..num_orders)
+            .map(|order| {
+                let colors: Vec<Color> = layers.iter().map(|pixel| pixel[order]).collect();
+                image_from_colors(&colors, screen_width as u16, screen_height as u16)
+            })
+            .collect()
+    }
+
+    // Saves get_order_layers in the directory as order_0.png (direct image), order_1.png, ...
+    pub fn export_order_layers(&self, num_orders: usize, directory: &Path) -> io::Result<()> {
+        fs::create_dir_all(directory)?;
+        for (order, image) in self.get_order_layers(num_orders).iter().enumerate() {
+            save_png(image, &directory.join(format!("order_{order}.png")))?;
+        }
+        Ok(())
+    }
+
+    // Image along with the Stokes parameters of each pixel row by row, see Ray::get_stokes
+    pub fn get_polarised_image(&self) -> (Image, Vec<Stokes>) {
+        let metric = self.metric;
+        let dλ0 = self.dλ0();
+        let stars = self.stars();
+        let pixels = self.render_values(move |camera, ray_direction, black_hole, skybox| {
+            get_pixel_stokes(
+                camera,
+                ray_direction,
+                black_hole,
+                metric,
+                dλ0,
+                skybox,
+                Arc::clone(&stars),
+            )
+        });
+
+        let (screen_width, screen_height) = self.screen_size().unpack();
+        let (colors, stokes): (Vec<Color>, Vec<Stokes>) = pixels.into_iter().unzip();
+        let image = image_from_colors(&colors, screen_width as u16, screen_height as u16);
+        (image, stokes)
+    }
+
+    // Saves get_polarised_image in the directory as image.png and stokes_i.png, stokes_q.png,
+    // stokes_u.png, stokes_v.png. All Stokes maps are scaled by the brightest pixel of I.
+    pub fn export_polarised_image(&self, directory: &Path) -> io::Result<()> {
+        let (image, stokes) = self.get_polarised_image();
+        let (width, height) = self.screen_size().unpack();
+        let (width, height) = (width as u16, height as u16);
+        let scale = stokes.iter().map(Stokes::i).fold(0., f64::max);
+
+        fs::create_dir_all(directory)?;
+        save_png(&image, &directory.join("image.png"))?;
+        for (index, name) in ["i", "q", "u", "v"].into_iter().enumerate() {
+            let values: Vec<f64> = stokes
+                .iter()
+                .map(|pixel| [pixel.i(), pixel.q(), pixel.u(), pixel.v()][index])
+                .collect();
+            // Only I is positive
+            let map = stokes_image(&values, scale, index > 0, width, height);
+            save_png(&map, &directory.join(format!("stokes_{name}.png")))?;
+        }
+        Ok(())
+    }
+
+    // Image along with the AOV buffers of its pixels, see Ray::get_color_with_record
+    pub fn get_image_with_aovs(&self) -> (Image, AovBuffers) {
+        let metric = self.metric;
+        let dλ0 = self.dλ0();
+        let stars = self.stars();
+        let pixels = self.render_values(move |camera, ray_direction, black_hole, skybox| {
+            get_pixel_record(
+                camera,
+                ray_direction,
+                black_hole,
+                metric,
+                dλ0,
+                skybox,
+                Arc::clone(&stars),
+            )
+        });
+
+        let (screen_width, screen_height) = self.screen_size().unpack();
+        let (width, height) = (screen_width as u16, screen_height as u16);
+        let (colors, records): (Vec<Color>, Vec<RayRecord>) = pixels.into_iter().unzip();
+        let image = image_from_colors(&colors, width, height);
+        (image, AovBuffers::from_records(&records, width, height))
+    }
+
+    // Saves get_image_with_aovs in the directory as color.png and one false-colour PNG per
+    // AOV, named after its AovMode
+    pub fn export_aovs(&self, directory: &Path) -> io::Result<()> {
+        let (image, aovs) = self.get_image_with_aovs();
+        fs::create_dir_all(directory)?;
+        let mut mode = AovMode::Color;
+        loop {
+            let map = aovs.visualise(mode).unwrap_or_else(|| image.clone());
+            save_png(&map, &directory.join(format!("{}.png", mode.name())))?;
+            mode = mode.next();
+            if mode == AovMode::Color {
+                return Ok(());
+            }
+        }
+    }
+
+    // Traces the ray of the pixel (px, py) on a screen of the given size, which needs no window,
+    // recording every step
+    pub fn trace_pixel(&self, px: f64, py: f64, screen_size: CartesianCoords2D) -> RayTrace {
+        let (screen_width, screen_height) = screen_size.unpack();
+        let aspect_ratio = screen_width / screen_height;
+        let scale = (f64::to_radians(crate::FOV) / 2.0).tan();
+        // Same normalised device coordinates as submit
+        let ndc_x = (px + 0.5) / screen_width * 2.0 - 1.0;
+        let ndc_y = 1.0 - 2.0 * (py + 0.5) / screen_height;
+        let ray_direction =
+            CartesianCoords3D::cartesian(ndc_x * scale * aspect_ratio, ndc_y * scale, 1.);
+        let ray = new_ray(
+            self.camera,
+            ray_direction,
+            self.metric,
+            self.dλ0,
+            self.stars(),
+        );
+        RayTrace::new(ray, self.black_hole, self.skybox())
+    }
+
+    // Traces a ray leaving the origin in the given world direction, seen by an observer moving
+    // like the camera, recording every step
+    pub fn trace_ray(&self, origin: CartesianCoords3D, direction: CartesianCoords3D) -> RayTrace {
+        let spatial_state = CartesianState3D::cartesian(
+            origin.x(),
+            origin.y(),
+            origin.z(),
+            direction.x(),
+            direction.y(),
+            direction.z(),
+        );
+        let ray = Ray::with_observer(spatial_state, self.camera.observer(), self.metric, self.dλ0)
+            .with_stars(self.stars());
+        RayTrace::new(ray, self.black_hole, self.skybox())
+    }
+
+    // Carries a spectrum along each ray instead of a colour, see Ray::get_spectral_color
+    pub fn get_image_spectral(&self) -> Image {
+        let metric = self.metric;
+        let dλ0 = self.dλ0();
+        let stars = self.stars();
+        self.render(move |camera, ray_direction, black_hole, skybox| {
+            get_pixel_color_spectral(
+                camera,
+                ray_direction,
+                black_hole,
+                metric,
+                dλ0,
+                skybox,
+                Arc::clone(&stars),
+            )
+        })
+    }
+
+    // Analytic edge of the shadow on the screen, in pixels, as a closed polyline. The camera
+    // position is taken as Boyer-Lindquist coordinates, so it only matches the traced shadow
+    // for the Schwarzschild, Kerr and Reissner-Nordström metrics.
+    pub fn critical_curve(&self) -> Vec<CartesianCoords2D> {
+        let (screen_width, screen_height) = self.screen_size().unpack();
+        let aspect_ratio = screen_width / screen_height;
+        let scale = (f64::to_radians(crate::FOV) / 2.0).tan();
+
+        let position = self.camera.position().to_spherical();
+        crate::shadow::critical_curve(self.black_hole, position, crate::CRITICAL_CURVE_POINTS)
+            .into_iter()
+            .filter_map(|state| {
+                let direction = pixel_direction(self.camera, state, self.metric)?;
+                let direction = self.camera.project(direction)?;
+                // Inverse of the normalised device coordinates of submit
+                let ndc_x = direction.x() / (scale * aspect_ratio);
+                let ndc_y = direction.y() / scale;
+                Some(CartesianCoords2D::cartesian(
+                    (ndc_x + 1.) / 2. * screen_width - 0.5,
+                    (1. - ndc_y) / 2. * screen_height - 0.5,
+                ))
+            })
+            .collect()
+    }
+
+    // Saves critical_curve as a CSV polyline of pixel coordinates, closed by its first point
+    pub fn export_critical_curve(&self, path: &Path) -> io::Result<()> {
+        let curve = self.critical_curve();
+        let mut polyline = String::from("x,y\n");
+        for point in curve.iter().chain(curve.first()) {
+            polyline.push_str(&format!("{},{}\n", point.x(), point.y()));
+        }
+        fs::write(path, polyline)
+    }
+
+    // Schwarzschild-only fast path, see PlanarRay
+    pub fn get_image_orbital_plane(&self) -> Image {
+        self.render(get_pixel_color_orbital_plane)
+    }
+
+    // Schwarzschild-only, integrates the rays once for the current camera radius
+    pub fn deflection_table(&self) -> DeflectionTable {
+        DeflectionTable::new(self.black_hole, self.camera.position().norm(), self.dλ0)
+    }
+
+    // Colours each pixel by table lookup. The camera may rotate freely, the table is only valid
+    // at its radius and is built again when the camera moved to another one.
+    pub fn get_image_from_table(&self, table: Arc<DeflectionTable>) -> Image {
+        let camera_radius = self.camera.position().norm();
+        let table = if (camera_radius - table.camera_radius()).abs() <= camera_radius * 1e-9 {
+            table
+        } else {
+            Arc::new(self.deflection_table())
+        };
+        self.render(move |camera, ray_direction, black_hole, skybox| {
+            table.get_color(camera_ray(camera, ray_direction), black_hole, skybox)
+        })
+    }
+
+    fn render<F>(&self, trace: F) -> Image
+    where
+        F: Fn(Camera, CartesianCoords3D, BlackHole, Arc<Skybox>) -> Color + Clone + Send + 'static,
+    {
+        let (screen_width, screen_height) = self.screen_size().unpack();
+        self.submit(trace)
+            .gather(screen_width as u16, screen_height as u16)
+    }
+
+    // Any per-pixel value, row by row
+    fn render_values<T, F>(&self, trace: F) -> Vec<T>
+    where
+        T: Send + 'static,
+        F: Fn(Camera, CartesianCoords3D, BlackHole, Arc<Skybox>) -> T + Clone + Send + 'static,
+    {
+        let (screen_width, screen_height) = self.screen_size().unpack();
+        self.submit(trace)
+            .gather_values(screen_width as u16, screen_height as u16)
+    }
+
+    // Queues the tracing of every pixel on a thread pool
+    fn submit<T, F>(&self, trace: F) -> crate::ThreadPool<T>
+    where
+        T: Send + 'static,
+        F: Fn(Camera, CartesianCoords3D, BlackHole, Arc<Skybox>) -> T + Clone + Send + 'static,
+    {
         let (screen_width, screen_height) = self.screen_size().unpack();
 
         let aspect_ratio = screen_width / screen_height;
@@ -190,13 +805,12 @@ impl Scene {
         let num_pixels = (screen_width * screen_height) as u32;
         let mut counter: u32 = 0;
 
-        let mut pool = crate::ThreadPool::new(crate::NUM_THREADS);
+        let pool = crate::ThreadPool::new(crate::NUM_THREADS);
 
         for px in 0..screen_width as u32 {
             let ndc_x = (px as f64 + 0.5) / (screen_width as f64) * 2.0 - 1.0;
             for py in 0..screen_height as u32 {
                 let ndc_y = 1.0 - 2.0 * (py as f64 + 0.5) / (screen_height as f64);
-                let dλ0 = self.dλ0();
                 let camera_clone = self.camera();
                 let black_hole = self.black_hole();
 
@@ -206,12 +820,13 @@ impl Scene {
                 let ray_direction =
                     CartesianCoords3D::cartesian(ndc_x * scale * aspect_ratio, ndc_y * scale, 1.);
                 let skybox = self.skybox();
+                let trace = trace.clone();
 
                 pool.execute(move || {
                     (
                         px,
                         py,
-                        get_pixel_color(camera_clone, ray_direction, black_hole, dλ0, skybox),
+                        trace(camera_clone, ray_direction, black_hole, skybox),
                     )
                 });
 
@@ -222,6 +837,6 @@ impl Scene {
             }
         }
 
-        pool.gather(screen_width as u16, screen_height as u16)
+        pool
     }
 }
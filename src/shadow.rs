@@ -0,0 +1,169 @@
+use std::f64::consts::PI;
+
+use crate::{BlackHole, SphericalCoords3D, SphericalState4D};
+
+// Edge of the shadow of a Kerr-Newman black hole seen from the given position: light reaching
+// the camera after grazing one of the spherical photon orbits. Returns a closed loop of ray
+// states at the camera in Boyer-Lindquist coordinates, with the velocity of the traced rays,
+// k = -p. Photons are labelled by their angular momentum ξ and Carter constant η, for E = 1.
+pub(crate) fn critical_curve(
+    black_hole: BlackHole,
+    position: SphericalCoords3D,
+    num_points: usize,
+) -> Vec<SphericalState4D> {
+    let num_points = num_points.max(4);
+    let constants: Vec<(f64, f64, f64)> = if black_hole.spin().abs()
+        < black_hole.mass() * crate::CRITICAL_CURVE_MIN_SPIN
+    {
+        // All orbits are on the photon sphere, with the impact parameter b² = η + ξ². The
+        // plane of the orbit, at the angle χ, sets ξ = b sin θ cos χ.
+        let impact_parameter = photon_sphere_impact_parameter(black_hole);
+        let sin_theta = position.theta().sin();
+        (0..num_points)
+            .map(|i| {
+                let chi = 2. * PI * i as f64 / num_points as f64;
+                let xi = impact_parameter * sin_theta * chi.cos();
+                let eta = impact_parameter.powi(2) - xi.powi(2);
+                (xi, eta, impact_parameter * chi.sin())
+            })
+            .collect()
+    } else {
+        let Some((r1, r2)) = visible_orbits(black_hole, position) else {
+            return Vec::new();
+        };
+        // Denser near both ends where the curve turns, along the upper half of the loop
+        // then back along the lower one
+        let half = num_points / 2;
+        let radius = |i: usize| r1 + 0.5 * (r2 - r1) * (1. - (PI * i as f64 / half as f64).cos());
+        let orbit = |i: usize, sign: f64| {
+            let (xi, eta) = spherical_photon_orbit(black_hole, radius(i));
+            let dtheta = theta_potential(black_hole, position, xi, eta)
+                .max(0.)
+                .sqrt();
+            (xi, eta, sign * dtheta)
+        };
+        (0..=half)
+            .map(|i| orbit(i, 1.))
+            .chain((1..half).rev().map(|i| orbit(i, -1.)))
+            .collect()
+    };
+
+    // Geodesic equations of Carter (1968) at the camera, with Σ = r² + a² cos²θ
+    let (mass, a, charge) = (black_hole.mass(), black_hole.spin(), black_hole.charge());
+    let r = position.r();
+    let (sin_theta, cos_theta) = position.theta().sin_cos();
+    let sigma = r.powi(2) + (a * cos_theta).powi(2);
+    let delta = (r.powi(2) - 2. * mass * r + a.powi(2) + charge.powi(2)).max(crate::DIV_EPSILON);
+    constants
+        .into_iter()
+        .filter_map(|(xi, eta, dtheta)| {
+            let p = r.powi(2) + a.powi(2) - a * xi;
+            let radial_potential = p.powi(2) - delta * (eta + (xi - a).powi(2));
+            if radial_potential < 0. {
+                return None;
+            }
+            let dt = (r.powi(2) + a.powi(2)) * p / delta - a * (a * sin_theta.powi(2) - xi);
+            let dphi = xi / sin_theta.powi(2).max(crate::DIV_EPSILON) - a + a * p / delta;
+            // Going out at the camera, then traced backwards
+            Some(SphericalState4D::spherical(
+                0.,
+                r,
+                position.theta(),
+                position.phi(),
+                -dt / sigma,
+                -radial_potential.sqrt() / sigma,
+                -dtheta / sigma,
+                -dphi / sigma,
+            ))
+        })
+        .collect()
+}
+
+// b = r² / √Δ at the photon sphere, where r Δ' = 4Δ
+fn photon_sphere_impact_parameter(black_hole: BlackHole) -> f64 {
+    let (mass, charge) = (black_hole.mass(), black_hole.charge());
+    let r = black_hole.photon_sphere_radius();
+    let delta = r.powi(2) - 2. * mass * r + charge.powi(2);
+    r.powi(2) / delta.max(crate::DIV_EPSILON).sqrt()
+}
+
+// (ξ, η) of the spherical photon orbit at r (Bardeen 1973), for a non-zero spin:
+// r² + a² - aξ = 4rΔ / Δ' and η + (ξ - a)² = 16r²Δ / Δ'², with Δ = r² - 2Mr + a² + Q²
+fn spherical_photon_orbit(black_hole: BlackHole, r: f64) -> (f64, f64) {
+    let (mass, a, charge) = (black_hole.mass(), black_hole.spin(), black_hole.charge());
+    let delta = r.powi(2) - 2. * mass * r + a.powi(2) + charge.powi(2);
+    let derivative = 2. * (r - mass);
+    let xi = (r.powi(2) + a.powi(2) - 4. * r * delta / derivative) / a;
+    let eta = 16. * r.powi(2) * delta / derivative.powi(2) - (xi - a).powi(2);
+    (xi, eta)
+}
+
+// Θ = η + a² cos²θ - ξ² cot²θ = (Σ dθ/dλ)², light can only reach the camera where it is positive
+fn theta_potential(black_hole: BlackHole, position: SphericalCoords3D, xi: f64, eta: f64) -> f64 {
+    let (sin_theta, cos_theta) = position.theta().sin_cos();
+    let cot_theta = cos_theta / sin_theta.max(crate::DIV_EPSILON);
+    eta + (black_hole.spin() * cos_theta).powi(2) - (xi * cot_theta).powi(2)
+}
+
+// Radii of the spherical photon orbits seen from the position, between the two where Θ vanishes
+fn visible_orbits(black_hole: BlackHole, position: SphericalCoords3D) -> Option<(f64, f64)> {
+    let visible = |r: f64| {
+        let (xi, eta) = spherical_photon_orbit(black_hole, r);
+        theta_potential(black_hole, position, xi, eta) >= 0.
+    };
+
+    // All photon orbits lie between the horizon and 4M
+    let steps = crate::CRITICAL_CURVE_SCAN_STEPS;
+    let (start, end) = (black_hole.horizon_radius(), 4. * black_hole.mass());
+    let radius = |i: usize| start + (end - start) * i as f64 / steps as f64;
+    let first = (1..steps).find(|&i| visible(radius(i)))?;
+    let last = (first..steps).rev().find(|&i| visible(radius(i)))?;
+
+    // Bisection between an invisible and a visible radius
+    let refine = |mut invisible: f64, mut inside: f64| {
+        for _ in 0..crate::CRITICAL_CURVE_BISECTIONS {
+            let middle = 0.5 * (invisible + inside);
+            if visible(middle) {
+                inside = middle;
+            } else {
+                invisible = middle;
+            }
+        }
+        inside
+    };
+    Some((
+        refine(radius(first - 1), radius(first)),
+        refine(radius(last + 1), radius(last)),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CartesianCoords4D, Metric, Schwarzschild};
+
+    #[test]
+    fn schwarzschild_critical_curve_has_impact_parameter_sqrt_27() {
+        let black_hole = BlackHole::new(CartesianCoords4D::cartesian(0., 0., 0., 0.), 1.);
+        let metric = Schwarzschild::from(black_hole);
+        let expected = 27f64.sqrt() * black_hole.mass();
+
+        for position in [
+            SphericalCoords3D::spherical(20., 0.5 * PI, 0.),
+            SphericalCoords3D::spherical(50., 1.1, -0.7),
+        ] {
+            let curve = critical_curve(black_hole, position, 64);
+            assert_eq!(curve.len(), 64);
+            for state in curve {
+                // E = -p_t and L² = p_θ² + p_φ² / sin²θ, with k = -p
+                let [energy, _, p_theta, p_phi] = metric.covariant_velocity(state);
+                let angular_momentum = p_theta.hypot(p_phi / state.theta().sin());
+                let impact_parameter = angular_momentum / energy;
+                assert!(
+                    (impact_parameter - expected).abs() < 1e-9 * expected,
+                    "impact parameter {impact_parameter}, expected {expected}"
+                );
+            }
+        }
+    }
+}
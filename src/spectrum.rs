@@ -0,0 +1,340 @@
+use std::ops::{Add, Mul};
+use std::sync::LazyLock;
+
+// Blackbody colours from the Planck spectrum integrated against the CIE 1931 2° colour-matching
+// functions. Colours are linear sRGB and keep the radiance of the blackbody, so that hotter
+// bodies are brighter and not just bluer.
+
+// Piecewise Gaussian with a different width on each side of the mean
+fn lobe(wavelength: f64, mean: f64, sigma_low: f64, sigma_high: f64) -> f64 {
+    let sigma = if wavelength < mean {
+        sigma_low
+    } else {
+        sigma_high
+    };
+    (-0.5 * ((wavelength - mean) / sigma).powi(2)).exp()
+}
+
+// CIE 1931 x̄, ȳ, z̄ at the given wavelength in nm, multi-lobe fit of Wyman, Sloan & Shirley (2013)
+pub fn color_matching(wavelength: f64) -> (f64, f64, f64) {
+    let x = 1.056 * lobe(wavelength, 599.8, 37.9, 31.0)
+        + 0.362 * lobe(wavelength, 442.0, 16.0, 26.7)
+        - 0.065 * lobe(wavelength, 501.1, 20.4, 26.2);
+    let y =
+        0.821 * lobe(wavelength, 568.8, 46.9, 40.5) + 0.286 * lobe(wavelength, 530.9, 16.3, 31.1);
+    let z =
+        1.217 * lobe(wavelength, 437.0, 11.8, 36.0) + 0.681 * lobe(wavelength, 459.0, 26.0, 13.8);
+    (x, y, z)
+}
+
+// Spectral radiance of a blackbody in W sr⁻¹ m⁻³, wavelength in nm
+pub fn planck(wavelength: f64, temperature: f64) -> f64 {
+    let wavelength = wavelength * 1e-9;
+    let h = crate::PLANCK_CONSTANT;
+    let c = crate::SPEED_OF_LIGHT;
+    let exponent =
+        h * c / (wavelength * crate::BOLTZMANN_CONSTANT * temperature.max(crate::DIV_EPSILON));
+    2. * h * c.powi(2) / (wavelength.powi(5) * exponent.exp_m1())
+}
+
+// Integrates a spectrum against the colour-matching functions over the visible range
+pub fn spectrum_to_xyz<F: Fn(f64) -> f64>(spectrum: F) -> (f64, f64, f64) {
+    let step = crate::SPECTRUM_WAVELENGTH_STEP;
+    let num_samples =
+        ((crate::SPECTRUM_MAX_WAVELENGTH - crate::SPECTRUM_MIN_WAVELENGTH) / step) as usize;
+
+    (0..=num_samples)
+        .map(|i| crate::SPECTRUM_MIN_WAVELENGTH + i as f64 * step)
+        .fold((0., 0., 0.), |(x, y, z), wavelength| {
+            let radiance = spectrum(wavelength) * step;
+            let (x_bar, y_bar, z_bar) = color_matching(wavelength);
+            (
+                x + radiance * x_bar,
+                y + radiance * y_bar,
+                z + radiance * z_bar,
+            )
+        })
+}
+
+pub fn xyz_to_linear_srgb((x, y, z): (f64, f64, f64)) -> (f64, f64, f64) {
+    (
+        3.2406 * x - 1.5372 * y - 0.4986 * z,
+        -0.9689 * x + 1.8758 * y + 0.0415 * z,
+        0.0557 * x - 0.2040 * y + 1.0570 * z,
+    )
+}
+
+// XYZ of blackbodies, log-uniformly sampled in temperature. Integrating the spectrum is too
+// slow to do for every disk hit.
+static BLACKBODY_XYZ: LazyLock<Vec<(f64, f64, f64)>> = LazyLock::new(|| {
+    (0..crate::BLACKBODY_TABLE_SIZE)
+        .map(|i| {
+            let temperature = blackbody_table_temperature(i as f64);
+            spectrum_to_xyz(|wavelength| planck(wavelength, temperature))
+        })
+        .collect()
+});
+
+fn blackbody_table_temperature(index: f64) -> f64 {
+    let (min, max) = (
+        crate::BLACKBODY_MIN_TEMPERATURE,
+        crate::BLACKBODY_MAX_TEMPERATURE,
+    );
+    min * (max / min).powf(index / (crate::BLACKBODY_TABLE_SIZE - 1) as f64)
+}
+
+pub fn blackbody_xyz(temperature: f64) -> (f64, f64, f64) {
+    let (min, max) = (
+        crate::BLACKBODY_MIN_TEMPERATURE,
+        crate::BLACKBODY_MAX_TEMPERATURE,
+    );
+    if temperature <= min {
+        return (0., 0., 0.);
+    }
+    let index = ((temperature / min).ln() / (max / min).ln()
+        * (crate::BLACKBODY_TABLE_SIZE - 1) as f64)
+        .min((crate::BLACKBODY_TABLE_SIZE - 1) as f64);
+    let i = (index as usize).min(crate::BLACKBODY_TABLE_SIZE - 2);
+    let fraction = index - i as f64;
+
+    let (x0, y0, z0) = BLACKBODY_XYZ[i];
+    let (x1, y1, z1) = BLACKBODY_XYZ[i + 1];
+    (
+        x0 + fraction * (x1 - x0),
+        y0 + fraction * (y1 - y0),
+        z0 + fraction * (z1 - z0),
+    )
+}
+
+// Linear sRGB of a blackbody, scaled so that a blackbody at the reference temperature has luminance 1.
+// Colours outside of the sRGB gamut are clipped to it.
+pub fn blackbody_rgb(temperature: f64, reference_temperature: f64) -> (f64, f64, f64) {
+    let (_, reference_luminance, _) = blackbody_xyz(reference_temperature);
+    let (x, y, z) = blackbody_xyz(temperature);
+    let scale = 1. / reference_luminance.max(f64::MIN_POSITIVE);
+
+    let (r, g, b) = xyz_to_linear_srgb((x * scale, y * scale, z * scale));
+    (r.max(0.), g.max(0.), b.max(0.))
+}
+
+// Radiance sampled in SPECTRUM_BINS equal wavelength bins over the visible range, in the same
+// unit as blackbody_rgb (a blackbody at the reference temperature has luminance 1)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Spectrum {
+    bins: [f64; crate::SPECTRUM_BINS],
+}
+
+// Colour-matching functions at the bin centers, times the bin width
+static BIN_COLOR_MATCHING: LazyLock<[(f64, f64, f64); crate::SPECTRUM_BINS]> =
+    LazyLock::new(|| {
+        std::array::from_fn(|i| {
+            let (x, y, z) = color_matching(Spectrum::wavelength(i));
+            let width = Spectrum::bin_width();
+            (x * width, y * width, z * width)
+        })
+    });
+
+// Spectra turning back into pure linear sRGB red, green and blue, built from three broad Gaussians
+// so that they stay smooth when shifted: s_c = Σ_k φ_k (M Φ)⁻¹_kc, with M projecting spectra to linear sRGB
+static RGB_BASIS: LazyLock<[Spectrum; 3]> = LazyLock::new(|| {
+    const CENTERS: [f64; 3] = [610., 545., 450.];
+    const WIDTH: f64 = 45.;
+    let gaussians: [Spectrum; 3] = CENTERS.map(|center| Spectrum {
+        bins: std::array::from_fn(|i| lobe(Spectrum::wavelength(i), center, WIDTH, WIDTH)),
+    });
+
+    let mut projection = [[0.; 3]; 3];
+    for (k, gaussian) in gaussians.iter().enumerate() {
+        let (r, g, b) = xyz_to_linear_srgb(gaussian.to_xyz());
+        projection[0][k] = r;
+        projection[1][k] = g;
+        projection[2][k] = b;
+    }
+    let inverse = invert_3x3(projection);
+
+    std::array::from_fn(|c| {
+        (0..3).fold(Spectrum::zero(), |spectrum, k| {
+            spectrum + gaussians[k] * inverse[k][c]
+        })
+    })
+});
+
+fn invert_3x3(m: [[f64; 3]; 3]) -> [[f64; 3]; 3] {
+    let cofactor = |i: usize, j: usize| {
+        let (r0, r1) = ((i + 1) % 3, (i + 2) % 3);
+        let (c0, c1) = ((j + 1) % 3, (j + 2) % 3);
+        m[r0][c0] * m[r1][c1] - m[r0][c1] * m[r1][c0]
+    };
+    let determinant: f64 = (0..3).map(|j| m[0][j] * cofactor(0, j)).sum();
+    std::array::from_fn(|i| std::array::from_fn(|j| cofactor(j, i) / determinant))
+}
+
+impl Spectrum {
+    pub fn zero() -> Self {
+        Self {
+            bins: [0.; crate::SPECTRUM_BINS],
+        }
+    }
+
+    fn bin_width() -> f64 {
+        (crate::SPECTRUM_MAX_WAVELENGTH - crate::SPECTRUM_MIN_WAVELENGTH)
+            / crate::SPECTRUM_BINS as f64
+    }
+
+    // Center of the bin in nm
+    pub fn wavelength(bin: usize) -> f64 {
+        crate::SPECTRUM_MIN_WAVELENGTH + (bin as f64 + 0.5) * Self::bin_width()
+    }
+
+    pub fn bins(&self) -> &[f64; crate::SPECTRUM_BINS] {
+        &self.bins
+    }
+
+    pub fn blackbody(temperature: f64, reference_temperature: f64) -> Self {
+        let (_, reference_luminance, _) = blackbody_xyz(reference_temperature);
+        let scale = 1. / reference_luminance.max(f64::MIN_POSITIVE);
+        Self {
+            bins: std::array::from_fn(|i| planck(Self::wavelength(i), temperature) * scale),
+        }
+    }
+
+    // Smooth spectrum with the given colour. It may go negative for saturated colours.
+    pub fn from_linear_srgb(r: f64, g: f64, b: f64) -> Self {
+        let [red, green, blue] = *RGB_BASIS;
+        red * r + green * g + blue * b
+    }
+
+    // Spectrum seen by an observer receiving this radiance with frequency ratio g = ν_obs / ν_emit.
+    // I_ν / ν³ is invariant, so I_λ(λ) = g⁵ I_λ,emitted(g λ). Radiance is moved across bins by
+    // sampling the emitted spectrum at g λ, extending it flat outside of the visible range.
+    pub fn redshifted(&self, g: f64) -> Self {
+        let boost = g.powi(5);
+        let last = (crate::SPECTRUM_BINS - 1) as f64;
+        Self {
+            bins: std::array::from_fn(|i| {
+                let emitted = g * Self::wavelength(i);
+                let index = ((emitted - crate::SPECTRUM_MIN_WAVELENGTH) / Self::bin_width() - 0.5)
+                    .clamp(0., last);
+                let lower = (index as usize).min(crate::SPECTRUM_BINS - 2);
+                let fraction = index - lower as f64;
+                let value = self.bins[lower] + fraction * (self.bins[lower + 1] - self.bins[lower]);
+                boost * value
+            }),
+        }
+    }
+
+    pub fn to_xyz(self) -> (f64, f64, f64) {
+        self.bins.iter().zip(BIN_COLOR_MATCHING.iter()).fold(
+            (0., 0., 0.),
+            |(x, y, z), (radiance, (x_bar, y_bar, z_bar))| {
+                (
+                    x + radiance * x_bar,
+                    y + radiance * y_bar,
+                    z + radiance * z_bar,
+                )
+            },
+        )
+    }
+
+    pub fn to_linear_srgb(self) -> (f64, f64, f64) {
+        let (r, g, b) = xyz_to_linear_srgb(self.to_xyz());
+        (r.max(0.), g.max(0.), b.max(0.))
+    }
+}
+
+impl Add for Spectrum {
+    type Output = Spectrum;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self {
+            bins: std::array::from_fn(|i| self.bins[i] + rhs.bins[i]),
+        }
+    }
+}
+
+impl Mul<f64> for Spectrum {
+    type Output = Spectrum;
+
+    fn mul(self, rhs: f64) -> Self::Output {
+        Self {
+            bins: self.bins.map(|value| value * rhs),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chromaticity((x, y, z): (f64, f64, f64)) -> (f64, f64) {
+        (x / (x + y + z), y / (x + y + z))
+    }
+
+    #[test]
+    fn planck_and_cie_give_the_standard_white_points() {
+        // Equal-energy illuminant E, Planckian locus at 6500 K and illuminant A (2856 K)
+        for (spectrum, expected, tolerance) in [
+            (
+                &(|_: f64| 1.) as &dyn Fn(f64) -> f64,
+                (1. / 3., 1. / 3.),
+                1e-3,
+            ),
+            (
+                &|wavelength| planck(wavelength, 6500.),
+                (0.3135, 0.3236),
+                1e-3,
+            ),
+            (
+                &|wavelength| planck(wavelength, 2856.),
+                (0.4476, 0.4074),
+                2e-3,
+            ),
+        ] {
+            let (x, y) = chromaticity(spectrum_to_xyz(spectrum));
+            assert!(
+                (x - expected.0).abs() < tolerance && (y - expected.1).abs() < tolerance,
+                "chromaticity ({x}, {y}), expected {expected:?}"
+            );
+        }
+
+        // D65 is white in sRGB
+        let (r, g, b) = xyz_to_linear_srgb((0.95047, 1., 1.08883));
+        for channel in [r, g, b] {
+            assert!((channel - 1.).abs() < 1e-3, "D65 gives ({r}, {g}, {b})");
+        }
+    }
+
+    #[test]
+    fn redshifted_blackbody_is_a_blackbody_at_g_times_the_temperature() {
+        // g⁵ B_λ(T)(g λ) = B_λ(g T)(λ), wherever g λ is still in the sampled range
+        let temperature = 6000.;
+        let spectrum = Spectrum::blackbody(temperature, temperature);
+        let (first, last) = (
+            Spectrum::wavelength(0),
+            Spectrum::wavelength(crate::SPECTRUM_BINS - 1),
+        );
+        for g in [0.9, 1.1] {
+            let shifted = spectrum.redshifted(g);
+            let expected = Spectrum::blackbody(g * temperature, temperature);
+            for i in 0..crate::SPECTRUM_BINS {
+                let emitted = g * Spectrum::wavelength(i);
+                if emitted < first || emitted > last {
+                    continue;
+                }
+                let (actual, expected) = (shifted.bins()[i], expected.bins()[i]);
+                assert!(
+                    (actual - expected).abs() < 1e-3 * expected,
+                    "g = {g}, bin {i}: {actual} != {expected}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn spectrum_from_linear_srgb_gives_the_colour_back() {
+        let (r, g, b) = Spectrum::from_linear_srgb(0.2, 0.5, 0.8).to_linear_srgb();
+        for (actual, expected) in [(r, 0.2), (g, 0.5), (b, 0.8)] {
+            assert!((actual - expected).abs() < 1e-9, "{actual} != {expected}");
+        }
+    }
+}
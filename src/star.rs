@@ -0,0 +1,231 @@
+use crate::spectrum::Spectrum;
+use crate::{BlackHole, CartesianCoords3D, CartesianCoords4D, CartesianState3D};
+use crate::{GeodesicState, Metric, Observer};
+use macroquad::prelude::Color;
+
+// Small emitting sphere moving freely along a timelike geodesic, like a star or a probe. Its
+// world line is integrated once, from far enough in the past for rays to see it where it was
+// when they left it, up to the given duration after t = 0. It doesn't exist outside of that
+// window, nor after it falls into the black hole.
+#[derive(Debug, Clone)]
+pub struct Star {
+    world_line: Vec<CartesianCoords4D>, // events in increasing coordinate time
+    radius: f64,
+    temperature: f64, // K, in the frame of the star
+}
+
+// Where a ray hit a star: the temperature of the star and the frequency ratio
+// g = E_observed / E_emitted
+#[derive(Debug, Clone, Copy)]
+pub struct StarHit {
+    temperature: f64,
+    redshift: f64,
+}
+
+impl StarHit {
+    pub fn new(temperature: f64, redshift: f64) -> Self {
+        Self {
+            temperature,
+            redshift,
+        }
+    }
+
+    pub fn temperature(&self) -> f64 {
+        self.temperature
+    }
+    pub fn redshift(&self) -> f64 {
+        self.redshift
+    }
+
+    // Opaque blackbody seen at the observed temperature g T, with luminance 1 when unshifted
+    pub fn get_color(&self) -> Color {
+        let (r, g, b) =
+            crate::spectrum::blackbody_rgb(self.temperature * self.redshift, self.temperature);
+        Color::new(r as f32, g as f32, b as f32, 1.)
+    }
+
+    pub fn get_spectrum(&self) -> Spectrum {
+        Spectrum::blackbody(self.temperature * self.redshift, self.temperature)
+    }
+}
+
+impl Star {
+    // Starts at t = 0 at the position with the given velocity relative to the Static observer,
+    // in units of c
+    pub fn new<S: GeodesicState, M: Metric<S>>(
+        black_hole: BlackHole,
+        metric: M,
+        position: CartesianCoords3D,
+        velocity: CartesianCoords3D,
+        duration: f64,
+    ) -> Self {
+        let state = S::from_spatial(CartesianState3D::cartesian(
+            position.x(),
+            position.y(),
+            position.z(),
+            0.,
+            0.,
+            0.,
+        ));
+        let state = state.with_velocity(Observer::Boost(velocity).velocity(&metric, state));
+
+        // Rays are traced at most through the bounding box and back
+        let history = crate::STAR_HISTORY_FACTOR * crate::BOUNDING_BOX_FACTOR * black_hole.radius();
+        let mut world_line = integrate_world_line(black_hole, &metric, state, -history);
+        world_line.reverse();
+        world_line.pop();
+        world_line.extend(integrate_world_line(black_hole, &metric, state, duration));
+
+        Self {
+            world_line,
+            radius: black_hole.radius() * crate::STAR_RADIUS_FACTOR,
+            temperature: crate::STAR_TEMPERATURE,
+        }
+    }
+
+    // Bound orbit starting at apoapsis on the negative x axis, with the Newtonian velocity of
+    // an ellipse of the given semi-major axis and eccentricity. The orbital plane is tilted from
+    // the equator by the inclination, around the x axis. Relativistic periapsis precession turns
+    // the ellipse into a rosette, like the orbit of S2 around Sgr A*.
+    pub fn elliptical<S: GeodesicState, M: Metric<S>>(
+        black_hole: BlackHole,
+        metric: M,
+        semi_major_axis: f64,
+        eccentricity: f64,
+        inclination: f64,
+        duration: f64,
+    ) -> Self {
+        let eccentricity = eccentricity.clamp(0., 1. - crate::DIV_EPSILON);
+        let apoapsis = semi_major_axis * (1. + eccentricity);
+        let speed = (black_hole.mass() / semi_major_axis * (1. - eccentricity)
+            / (1. + eccentricity))
+            .sqrt();
+        let (sin_i, cos_i) = inclination.sin_cos();
+        Self::new(
+            black_hole,
+            metric,
+            CartesianCoords3D::cartesian(-apoapsis, 0., 0.),
+            CartesianCoords3D::cartesian(0., -speed * cos_i, -speed * sin_i),
+            duration,
+        )
+    }
+
+    pub fn with_radius(self, radius: f64) -> Self {
+        Self {
+            radius: radius.max(crate::DIV_EPSILON),
+            ..self
+        }
+    }
+
+    pub fn with_temperature(self, temperature: f64) -> Self {
+        Self {
+            temperature: temperature.max(0.),
+            ..self
+        }
+    }
+
+    pub fn radius(&self) -> f64 {
+        self.radius
+    }
+    pub fn temperature(&self) -> f64 {
+        self.temperature
+    }
+    pub fn world_line(&self) -> &[CartesianCoords4D] {
+        &self.world_line
+    }
+
+    // Position of the center and velocity dx/dt at the given coordinate time, along the
+    // straight segments between the integrated events
+    pub fn state_at(&self, time: f64) -> Option<(CartesianCoords3D, CartesianCoords3D)> {
+        let next = self.world_line.partition_point(|event| event.t() < time);
+        if next == 0 || next == self.world_line.len() {
+            return None;
+        }
+        let (before, after) = (self.world_line[next - 1], self.world_line[next]);
+        let duration = (after.t() - before.t()).max(crate::DIV_EPSILON);
+        let velocity = (after.position() - before.position()) * (1. / duration);
+        Some((before.position() + velocity * (time - before.t()), velocity))
+    }
+
+    // Fraction of the segment where it enters the star, and the velocity of the star then.
+    // Times are the coordinate times at which light left both ends of the segment, the star is
+    // taken where it was when light left the entry point.
+    pub fn check_intersection(
+        &self,
+        position1: CartesianCoords3D,
+        position2: CartesianCoords3D,
+        time1: f64,
+        time2: f64,
+    ) -> Option<(f64, CartesianCoords3D)> {
+        let segment = position2 - position1;
+        let squared_length = segment.dot(segment);
+        if squared_length < crate::DIV_EPSILON {
+            return None;
+        }
+
+        // First with the star at the middle of the segment, then at the entry point found
+        let mut entry = (0.5, CartesianCoords3D::cartesian(0., 0., 0.));
+        for _ in 0..crate::STAR_INTERSECTION_ITERATIONS {
+            let (center, velocity) = self.state_at(time1 + entry.0 * (time2 - time1))?;
+            // |position1 + t segment - center|² = radius²
+            let offset = position1 - center;
+            let b = offset.dot(segment) / squared_length;
+            let c = (offset.dot(offset) - self.radius.powi(2)) / squared_length;
+            let discriminant = b.powi(2) - c;
+            if discriminant < 0. {
+                return None;
+            }
+            let fraction = -b - discriminant.sqrt();
+            entry = if c <= 0. {
+                // The segment starts inside the star
+                (0., velocity)
+            } else if (0. ..=1.).contains(&fraction) {
+                (fraction, velocity)
+            } else {
+                return None;
+            };
+        }
+        Some(entry)
+    }
+}
+
+// Events of the geodesic of a massive particle, integrated with u.u = -1 until the coordinate
+// time reaches the given one, backwards in time if it is negative. Steps are a fraction of
+// the radius so that the straight segments between the events follow the orbit closely.
+fn integrate_world_line<S: GeodesicState, M: Metric<S>>(
+    black_hole: BlackHole,
+    metric: &M,
+    state: S,
+    time: f64,
+) -> Vec<CartesianCoords4D> {
+    // Boyer-Lindquist time elapsed since the initial state, like the delays of rays
+    let start = metric.coordinate_time(state);
+    let elapsed = |state: S| metric.coordinate_time(state) - start;
+    let event = |state: S| {
+        let position = metric.cartesian_position(state);
+        CartesianCoords4D::cartesian(elapsed(state), position.x(), position.y(), position.z())
+    };
+    // Reversing the 4-velocity runs along the same geodesic, backwards in time
+    let direction = time.signum();
+    let orient = |state: S| state.with_velocity(state.velocity().map(|v| direction * v));
+
+    let mut state = orient(state);
+    let mut dτ = black_hole.radius() * crate::INTEGRATION_STEP_FACTOR;
+    let mut world_line = vec![event(state)];
+    while direction * elapsed(state) < direction * time
+        && metric.radius(state) > black_hole.visual_radius()
+        && world_line.len() < crate::STAR_MAX_EVENTS
+    {
+        let step = dτ.min(metric.radius(state) * crate::STAR_STEP_FACTOR);
+        let Ok((next, next_dτ)) =
+            crate::geodesic::solve_geodesic_rkf45(state, metric, black_hole.radius(), step)
+        else {
+            break;
+        };
+        // renormalize_timelike keeps the future-directed 4-velocity
+        state = orient(metric.renormalize_timelike(orient(next)));
+        dτ = next_dτ;
+        world_line.push(event(state));
+    }
+    world_line
+}
@@ -0,0 +1,237 @@
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+use crate::Tensor4D;
+
+// Forward-mode autodiff scalar. The gradient holds the partial derivatives
+// with respect to the four coordinates, so a single evaluation gives all of them.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Dual {
+    value: f64,
+    gradient: Tensor4D,
+}
+
+impl Dual {
+    pub fn new(value: f64, gradient: Tensor4D) -> Self {
+        Self { value, gradient }
+    }
+
+    pub fn constant(value: f64) -> Self {
+        Self::new(value, Tensor4D::new(0., 0., 0., 0.))
+    }
+
+    // Seeds each coordinate with its own unit gradient
+    pub fn variables(a: f64, b: f64, c: f64, d: f64) -> [Self; 4] {
+        [
+            Self::new(a, Tensor4D::new(1., 0., 0., 0.)),
+            Self::new(b, Tensor4D::new(0., 1., 0., 0.)),
+            Self::new(c, Tensor4D::new(0., 0., 1., 0.)),
+            Self::new(d, Tensor4D::new(0., 0., 0., 1.)),
+        ]
+    }
+
+    pub fn value(&self) -> f64 {
+        self.value
+    }
+
+    pub fn gradient(&self) -> Tensor4D {
+        self.gradient
+    }
+
+    // Chain rule: f(x) with f'(x) given
+    fn chain(self, value: f64, derivative: f64) -> Self {
+        Self::new(value, self.gradient * derivative)
+    }
+
+    pub fn sin(self) -> Self {
+        let (sin, cos) = self.value.sin_cos();
+        self.chain(sin, cos)
+    }
+
+    pub fn cos(self) -> Self {
+        let (sin, cos) = self.value.sin_cos();
+        self.chain(cos, -sin)
+    }
+
+    pub fn sin_cos(self) -> (Self, Self) {
+        let (sin, cos) = self.value.sin_cos();
+        (self.chain(sin, cos), self.chain(cos, -sin))
+    }
+
+    pub fn sqrt(self) -> Self {
+        let sqrt = self.value.sqrt();
+        self.chain(sqrt, 0.5 / sqrt)
+    }
+
+    pub fn powi(self, n: i32) -> Self {
+        // x⁰ is constant, even at x = 0 where x⁻¹ is infinite
+        if n == 0 {
+            return Self::constant(1.);
+        }
+        self.chain(self.value.powi(n), n as f64 * self.value.powi(n - 1))
+    }
+
+    pub fn powf(self, n: f64) -> Self {
+        self.chain(self.value.powf(n), n * self.value.powf(n - 1.))
+    }
+
+    pub fn exp(self) -> Self {
+        let exp = self.value.exp();
+        self.chain(exp, exp)
+    }
+
+    pub fn ln(self) -> Self {
+        self.chain(self.value.ln(), 1. / self.value)
+    }
+}
+
+// Arithmetic shared by f64 and Dual, for formulas needed both as values and with their
+// derivatives
+pub trait Scalar:
+    Copy
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Sub<f64, Output = Self>
+    + Mul<f64, Output = Self>
+{
+    fn sqrt(self) -> Self;
+    fn powi(self, n: i32) -> Self;
+}
+
+impl Scalar for f64 {
+    fn sqrt(self) -> Self {
+        f64::sqrt(self)
+    }
+
+    fn powi(self, n: i32) -> Self {
+        f64::powi(self, n)
+    }
+}
+
+impl Scalar for Dual {
+    fn sqrt(self) -> Self {
+        Dual::sqrt(self)
+    }
+
+    fn powi(self, n: i32) -> Self {
+        Dual::powi(self, n)
+    }
+}
+
+impl From<f64> for Dual {
+    fn from(value: f64) -> Self {
+        Self::constant(value)
+    }
+}
+
+impl Neg for Dual {
+    type Output = Dual;
+
+    fn neg(self) -> Self::Output {
+        Self::new(-self.value, self.gradient * -1.)
+    }
+}
+
+impl Add for Dual {
+    type Output = Dual;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self::new(self.value + rhs.value, self.gradient + rhs.gradient)
+    }
+}
+
+impl Sub for Dual {
+    type Output = Dual;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self::new(self.value - rhs.value, self.gradient - rhs.gradient)
+    }
+}
+
+impl Mul for Dual {
+    type Output = Dual;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        Self::new(
+            self.value * rhs.value,
+            self.gradient * rhs.value + rhs.gradient * self.value,
+        )
+    }
+}
+
+impl Div for Dual {
+    type Output = Dual;
+
+    fn div(self, rhs: Self) -> Self::Output {
+        // Tensor division clamps the denominator, so multiply by the inverse instead
+        let inverse = 1. / rhs.value;
+        Self::new(
+            self.value * inverse,
+            (self.gradient - rhs.gradient * (self.value * inverse)) * inverse,
+        )
+    }
+}
+
+impl Add<f64> for Dual {
+    type Output = Dual;
+
+    fn add(self, rhs: f64) -> Self::Output {
+        Self::new(self.value + rhs, self.gradient)
+    }
+}
+
+impl Sub<f64> for Dual {
+    type Output = Dual;
+
+    fn sub(self, rhs: f64) -> Self::Output {
+        Self::new(self.value - rhs, self.gradient)
+    }
+}
+
+impl Mul<f64> for Dual {
+    type Output = Dual;
+
+    fn mul(self, rhs: f64) -> Self::Output {
+        Self::new(self.value * rhs, self.gradient * rhs)
+    }
+}
+
+impl Div<f64> for Dual {
+    type Output = Dual;
+
+    fn div(self, rhs: f64) -> Self::Output {
+        self * (1. / rhs)
+    }
+}
+
+impl Add<Dual> for f64 {
+    type Output = Dual;
+
+    fn add(self, rhs: Dual) -> Self::Output {
+        rhs + self
+    }
+}
+
+impl Sub<Dual> for f64 {
+    type Output = Dual;
+
+    fn sub(self, rhs: Dual) -> Self::Output {
+        -rhs + self
+    }
+}
+
+impl Mul<Dual> for f64 {
+    type Output = Dual;
+
+    fn mul(self, rhs: Dual) -> Self::Output {
+        rhs * self
+    }
+}
+
+impl Div<Dual> for f64 {
+    type Output = Dual;
+
+    fn div(self, rhs: Dual) -> Self::Output {
+        Dual::constant(self) / rhs
+    }
+}
@@ -1,5 +1,6 @@
-use std::ops::Div;
+use std::ops::{Add, Div, Mul, Sub};
 
+mod dual;
 mod tensor2d;
 mod tensor3d;
 mod tensor4d;
@@ -11,6 +12,7 @@ pub struct Spherical;
 #[derive(Debug, Clone, Copy)]
 pub struct Cartesian;
 
+pub use dual::*;
 pub use tensor2d::*;
 pub use tensor3d::*;
 pub use tensor4d::*;
@@ -24,3 +26,36 @@ pub trait Norm: Div<f64, Output = Self> + Copy {
         self / self.norm()
     }
 }
+
+// 8-component geodesic state (position then velocity) that the integrator and the ray can work with
+pub trait GeodesicState:
+    Add<Output = Self> + Sub<Output = Self> + Mul<f64, Output = Self> + Norm + Send + 'static
+{
+    type Position: Copy;
+
+    // Builds a state from a spatial position and velocity, with zero time component
+    fn from_spatial(spatial_state: CartesianState3D) -> Self;
+
+    fn coordinates(&self) -> Self::Position;
+
+    // Coordinate time t, the camera sits at t = 0
+    fn time(&self) -> f64;
+
+    // Coordinate 4-velocity, time component first
+    fn velocity(&self) -> [f64; 4];
+
+    fn with_dt(&self, dt: f64) -> Self;
+
+    // Same position with the given coordinate 4-velocity
+    fn with_velocity(&self, velocity: [f64; 4]) -> Self;
+
+    // Distance from the origin of the coordinate system
+    fn radius(&self) -> f64;
+
+    fn cartesian_position(&self) -> CartesianCoords3D;
+
+    fn is_receding(&self) -> bool;
+
+    // Components of the Killing vector ∂φ generating rotations around the z axis
+    fn rotation_generator(&self) -> [f64; 4];
+}
@@ -77,6 +77,12 @@ impl<Kind: Copy> Div for _Tensor2D<Kind> {
     }
 }
 
+impl<Kind: Copy> super::Norm for _Tensor2D<Kind> {
+    fn norm(&self) -> f64 {
+        f64::sqrt(self.a * self.a + self.b * self.b)
+    }
+}
+
 impl<Kind: Copy, T: Into<f64>> Div<T> for _Tensor2D<Kind> {
     type Output = _Tensor2D<Kind>;
 
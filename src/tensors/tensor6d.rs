@@ -1,7 +1,7 @@
 use std::marker::PhantomData;
 use std::ops::{Add, Div, Mul, Sub};
 
-use crate::{CartesianCoords3D, SphericalCoords3D, SphericalState4D};
+use crate::{CartesianCoords3D, Metric, SphericalCoords3D, SphericalState4D};
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct _Tensor6D<Kind> {
@@ -212,7 +212,7 @@ impl SphericalState3D {
         SphericalCoords3D::spherical(self.r(), self.theta(), self.phi())
     }
 
-    pub fn to_4d(&self, rs: f64) -> SphericalState4D {
+    pub fn to_4d<M: Metric>(&self, metric: &M) -> SphericalState4D {
         SphericalState4D::spherical(
             0.,
             self.r(),
@@ -223,7 +223,7 @@ impl SphericalState3D {
             self.dtheta(),
             self.dphi(),
         )
-        .renormalize(rs)
+        .renormalize(metric)
     }
 
     pub fn to_cartesian(&self) -> CartesianState3D {
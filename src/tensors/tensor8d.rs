@@ -1,7 +1,10 @@
 use std::marker::PhantomData;
 use std::ops::{Add, Div, Mul, Sub};
 
-use crate::{CartesianCoords4D, SphericalCoords3D, SphericalCoords4D};
+use crate::{
+    CartesianCoords3D, CartesianCoords4D, CartesianState3D, GeodesicState, Metric, Norm,
+    SphericalCoords3D, SphericalCoords4D,
+};
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct _Tensor8D<Kind> {
@@ -265,28 +268,8 @@ impl SphericalState4D {
         SphericalCoords3D::spherical(self.dr(), self.dtheta(), self.dphi())
     }
 
-    pub fn renormalize(self, rs: f64) -> Self {
-        let r = self.r();
-        let r2 = r.powi(2);
-        let theta = self.theta();
-
-        let denom = (1.0 - rs / r).max(crate::DIV_EPSILON);
-
-        let num_part1 = (1.0 / denom) * self.dr().powi(2);
-        let num_part2 = r2 * self.dtheta().powi(2);
-        let num_part3 = r2 * (theta.sin() * self.dphi()).powi(2);
-
-        let dt = f64::sqrt((num_part1 + num_part2 + num_part3) / denom);
-        Self::spherical(
-            self.t(),
-            self.r(),
-            self.theta(),
-            self.phi(),
-            dt,
-            self.dr(),
-            self.dtheta(),
-            self.dphi(),
-        )
+    pub fn renormalize<M: Metric>(self, metric: &M) -> Self {
+        metric.renormalize(self)
     }
 
     pub fn to_cartesian(&self) -> CartesianState4D {
@@ -417,6 +400,126 @@ impl CartesianState4D {
     }
 }
 
+impl GeodesicState for SphericalState4D {
+    type Position = SphericalCoords4D;
+
+    fn from_spatial(spatial_state: CartesianState3D) -> Self {
+        let spherical = spatial_state.to_spherical();
+        Self::spherical(
+            0.,
+            spherical.r(),
+            spherical.theta(),
+            spherical.phi(),
+            0.,
+            spherical.dr(),
+            spherical.dtheta(),
+            spherical.dphi(),
+        )
+    }
+
+    fn coordinates(&self) -> SphericalCoords4D {
+        self.position()
+    }
+
+    fn time(&self) -> f64 {
+        self.t()
+    }
+
+    fn velocity(&self) -> [f64; 4] {
+        [self.dt(), self.dr(), self.dtheta(), self.dphi()]
+    }
+
+    fn with_dt(&self, dt: f64) -> Self {
+        let mut state = *self;
+        *state.dt_mut() = dt;
+        state
+    }
+
+    fn with_velocity(&self, [dt, dr, dtheta, dphi]: [f64; 4]) -> Self {
+        Self::spherical(
+            self.t(),
+            self.r(),
+            self.theta(),
+            self.phi(),
+            dt,
+            dr,
+            dtheta,
+            dphi,
+        )
+    }
+
+    fn radius(&self) -> f64 {
+        self.r()
+    }
+
+    fn cartesian_position(&self) -> CartesianCoords3D {
+        self.spatial_position().to_cartesian()
+    }
+
+    fn is_receding(&self) -> bool {
+        self.spatial_position().dot(self.spatial_velocity()) > 0.
+    }
+
+    fn rotation_generator(&self) -> [f64; 4] {
+        [0., 0., 0., 1.]
+    }
+}
+
+impl GeodesicState for CartesianState4D {
+    type Position = CartesianCoords4D;
+
+    fn from_spatial(spatial_state: CartesianState3D) -> Self {
+        Self::cartesian(
+            0.,
+            spatial_state.x(),
+            spatial_state.y(),
+            spatial_state.z(),
+            0.,
+            spatial_state.dx(),
+            spatial_state.dy(),
+            spatial_state.dz(),
+        )
+    }
+
+    fn coordinates(&self) -> CartesianCoords4D {
+        self.position()
+    }
+
+    fn time(&self) -> f64 {
+        self.t()
+    }
+
+    fn velocity(&self) -> [f64; 4] {
+        [self.dt(), self.dx(), self.dy(), self.dz()]
+    }
+
+    fn with_dt(&self, dt: f64) -> Self {
+        let mut state = *self;
+        *state.dt_mut() = dt;
+        state
+    }
+
+    fn with_velocity(&self, [dt, dx, dy, dz]: [f64; 4]) -> Self {
+        Self::cartesian(self.t(), self.x(), self.y(), self.z(), dt, dx, dy, dz)
+    }
+
+    fn radius(&self) -> f64 {
+        self.cartesian_position().norm()
+    }
+
+    fn cartesian_position(&self) -> CartesianCoords3D {
+        CartesianCoords3D::cartesian(self.x(), self.y(), self.z())
+    }
+
+    fn is_receding(&self) -> bool {
+        self.x() * self.dx() + self.y() * self.dy() + self.z() * self.dz() > 0.
+    }
+
+    fn rotation_generator(&self) -> [f64; 4] {
+        [0., -self.y(), self.x(), 0.]
+    }
+}
+
 impl From<SphericalState4D> for CartesianState4D {
     fn from(value: SphericalState4D) -> Self {
         value.to_cartesian()
@@ -4,19 +4,20 @@ use std::thread;
 use macroquad::color::{BLACK, Color};
 use macroquad::texture::Image;
 
-type Job = Box<dyn FnOnce() -> (u32, u32, Color) + Send + 'static>;
+type Job<T> = Box<dyn FnOnce() -> (u32, u32, T) + Send + 'static>;
 
-pub struct ThreadPool {
+// Computes one value per pixel, a color unless told otherwise
+pub struct ThreadPool<T = Color> {
     threads: Vec<thread::JoinHandle<()>>,
-    sender: Option<mpsc::Sender<Job>>,
-    res_receiver: Option<mpsc::Receiver<(u32, u32, Color)>>,
+    sender: Option<mpsc::Sender<Job<T>>>,
+    res_receiver: Option<mpsc::Receiver<(u32, u32, T)>>,
 }
 
-impl ThreadPool {
+impl<T: Send + 'static> ThreadPool<T> {
     pub fn new(num_threads: u32) -> Self {
         let mut threads = Vec::with_capacity(num_threads as usize);
 
-        let (sender, receiver) = mpsc::channel::<Job>();
+        let (sender, receiver) = mpsc::channel::<Job<T>>();
         let receiver = Arc::new(Mutex::new(receiver));
 
         let (res_sender, res_receiver) = mpsc::channel();
@@ -43,7 +44,7 @@ impl ThreadPool {
 
     pub fn execute<F>(&self, f: F)
     where
-        F: FnOnce() -> (u32, u32, Color) + Send + 'static,
+        F: FnOnce() -> (u32, u32, T) + Send + 'static,
     {
         let job = Box::new(f);
         if let Some(sender) = self.sender.as_ref() {
@@ -51,6 +52,29 @@ impl ThreadPool {
         }
     }
 
+    // Values of all pixels row by row
+    pub fn gather_values(&mut self, width: u16, height: u16) -> Vec<T> {
+        let total_pixels = width as usize * height as usize;
+        let mut values: Vec<Option<T>> = (0..total_pixels).map(|_| None).collect();
+
+        if let Some(receiver) = self.res_receiver.take() {
+            for idx in 0..total_pixels {
+                let (x, y, value) = receiver.recv().unwrap();
+                values[y as usize * width as usize + x as usize] = Some(value);
+                if idx % 10_000 == 0 {
+                    println!("Solved {idx} / {total_pixels}");
+                }
+            }
+        }
+
+        values
+            .into_iter()
+            .map(|value| value.expect("every pixel has been computed"))
+            .collect()
+    }
+}
+
+impl ThreadPool<Color> {
     pub fn gather(&mut self, width: u16, height: u16) -> Image {
         let mut image = Image::gen_image_color(width, height, BLACK);
         let total_pixels = (width as u32) * (height as u32);
@@ -69,7 +93,7 @@ impl ThreadPool {
     }
 }
 
-impl Drop for ThreadPool {
+impl<T> Drop for ThreadPool<T> {
     fn drop(&mut self) {
         drop(self.sender.take());
         for thread in &mut self.threads.drain(..) {